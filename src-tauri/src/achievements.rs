@@ -0,0 +1,207 @@
+use crate::life::{Life, LifeStage};
+use crate::pet::{PetState, PetStats};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Observed time without a Critical state needed for `CalmWeek`, in seconds
+const CALM_SECS: f64 = 7.0 * 24.0 * 3600.0;
+/// Space freed by cleanups needed for `SpringCleaning`, in MB
+const SPRING_CLEANING_MB: u64 = 10 * 1024;
+/// Longest critical episode that still counts for `QuickRecovery`, in seconds
+const QUICK_RECOVERY_SECS: f64 = 5.0 * 60.0;
+
+/// Every achievement the pet can earn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AchievementId {
+    FirstCleanup,
+    SpringCleaning,
+    QuickRecovery,
+    CalmWeek,
+    Hatched,
+    Elder,
+}
+
+impl AchievementId {
+    /// All achievements, in the order they are listed
+    pub const ALL: [AchievementId; 6] = [
+        AchievementId::FirstCleanup,
+        AchievementId::SpringCleaning,
+        AchievementId::QuickRecovery,
+        AchievementId::CalmWeek,
+        AchievementId::Hatched,
+        AchievementId::Elder,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AchievementId::FirstCleanup => "Tidy Up",
+            AchievementId::SpringCleaning => "Spring Cleaning",
+            AchievementId::QuickRecovery => "First Responder",
+            AchievementId::CalmWeek => "Calm Week",
+            AchievementId::Hatched => "Hatched",
+            AchievementId::Elder => "Old Timer",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            AchievementId::FirstCleanup => "Run a cleanup",
+            AchievementId::SpringCleaning => "Free 10 GB with cleanups",
+            AchievementId::QuickRecovery => "Recover from Critical in under 5 minutes",
+            AchievementId::CalmWeek => "Go 7 days without a Critical state",
+            AchievementId::Hatched => "Hatch from the egg",
+            AchievementId::Elder => "Grow into an elder",
+        }
+    }
+}
+
+/// An achievement as shown to the user
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Achievement {
+    pub id: AchievementId,
+    pub name: &'static str,
+    pub description: &'static str,
+    /// When it was earned, in seconds since the Unix epoch
+    pub unlocked_at_unix_secs: Option<u64>,
+    /// How close it is, from 0 to 1
+    pub progress: f32,
+}
+
+/// Achievements earned so far, kept between sessions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Achievements {
+    /// When each earned achievement was unlocked, in seconds since the Unix
+    /// epoch
+    pub unlocked: BTreeMap<AchievementId, u64>,
+    /// Time the app has watched the pet since it was last Critical, in
+    /// seconds; time with the app closed does not count
+    pub secs_without_critical: f64,
+}
+
+impl Achievements {
+    pub fn is_unlocked(&self, id: AchievementId) -> bool {
+        self.unlocked.contains_key(&id)
+    }
+
+    /// How close `id` is, from 0 to 1
+    ///
+    /// A quick recovery either happened or not, so it only reaches 1 once
+    /// unlocked.
+    pub fn progress(&self, id: AchievementId, stats: PetStats, life: &Life) -> f32 {
+        if self.is_unlocked(id) {
+            return 1.0;
+        }
+        let ratio = match id {
+            AchievementId::FirstCleanup => stats.cleanups as f64,
+            AchievementId::SpringCleaning => stats.cleaned_mb as f64 / SPRING_CLEANING_MB as f64,
+            AchievementId::QuickRecovery => 0.0,
+            AchievementId::CalmWeek => self.secs_without_critical / CALM_SECS,
+            AchievementId::Hatched if life.stage > LifeStage::Egg => 1.0,
+            AchievementId::Hatched => 0.0,
+            AchievementId::Elder => life.stage as u8 as f64 / LifeStage::Elder as u8 as f64,
+        };
+        ratio.clamp(0.0, 1.0) as f32
+    }
+
+    /// Every achievement with its unlock time and progress
+    pub fn list(&self, stats: PetStats, life: &Life) -> Vec<Achievement> {
+        AchievementId::ALL
+            .iter()
+            .map(|&id| Achievement {
+                id,
+                name: id.name(),
+                description: id.description(),
+                unlocked_at_unix_secs: self.unlocked.get(&id).copied(),
+                progress: self.progress(id, stats, life),
+            })
+            .collect()
+    }
+}
+
+/// Achievements plus the clock they advance on
+#[derive(Debug, Clone, Default)]
+pub struct AchievementTracker {
+    achievements: Achievements,
+    /// When the last update happened and the state the pet was left in
+    last_tick: Option<(Instant, PetState)>,
+    /// Start of the current critical episode
+    critical_since: Option<Instant>,
+    /// Unlocked and not yet taken by `take_recent`
+    recent: Vec<AchievementId>,
+}
+
+impl AchievementTracker {
+    pub fn new(achievements: Achievements) -> Self {
+        Self {
+            achievements,
+            ..Default::default()
+        }
+    }
+
+    pub fn achievements(&self) -> &Achievements {
+        &self.achievements
+    }
+
+    /// Replace the achievements, e.g. with ones restored from a save file
+    pub fn set_achievements(&mut self, achievements: Achievements) {
+        self.achievements = achievements;
+        self.critical_since = None;
+    }
+
+    /// Achievements unlocked since the last `take_recent`
+    pub fn recent(&self) -> &[AchievementId] {
+        &self.recent
+    }
+
+    /// Hand over the achievements unlocked since the last call, so each is
+    /// announced once however many updates ran in between
+    pub fn take_recent(&mut self) -> Vec<AchievementId> {
+        std::mem::take(&mut self.recent)
+    }
+
+    /// Advance to `now`, the pet being in `state` from now on, and unlock
+    /// whatever has been earned
+    pub fn update_at(&mut self, state: PetState, stats: PetStats, life: &Life, now: Instant) {
+        let progress = &mut self.achievements;
+        if let Some((last, last_state)) = self.last_tick {
+            if last_state != PetState::Critical {
+                progress.secs_without_critical += now.saturating_duration_since(last).as_secs_f64();
+            }
+        }
+        self.last_tick = Some((now, state));
+
+        let mut recovered_quickly = false;
+        match (state == PetState::Critical, self.critical_since) {
+            (true, None) => self.critical_since = Some(now),
+            (false, Some(since)) => {
+                let secs = now.saturating_duration_since(since).as_secs_f64();
+                recovered_quickly = secs < QUICK_RECOVERY_SECS;
+                self.critical_since = None;
+            }
+            _ => {}
+        }
+        if state == PetState::Critical {
+            progress.secs_without_critical = 0.0;
+        }
+
+        let earned: Vec<_> = AchievementId::ALL
+            .into_iter()
+            .filter(|&id| !progress.is_unlocked(id))
+            .filter(|&id| match id {
+                AchievementId::QuickRecovery => recovered_quickly,
+                _ => progress.progress(id, stats, life) >= 1.0,
+            })
+            .collect();
+
+        let unix_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        for &id in &earned {
+            progress.unlocked.insert(id, unix_secs);
+        }
+        self.recent.extend(earned);
+    }
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Where the capacity a percentage is measured against comes from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitSource {
+    /// The whole machine
+    #[default]
+    Host,
+    /// A cgroup v2 limit lower than the machine (container, systemd slice)
+    Cgroup,
+}
+
+/// Capacity that RAM and CPU percentages are computed against, reported in `Metrics`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EffectiveLimits {
+    pub memory_source: LimitSource,
+    /// Memory limit in MB when it comes from a cgroup
+    pub memory_limit_mb: Option<u64>,
+    pub cpu_source: LimitSource,
+    /// CPU quota in cores when it comes from a cgroup
+    pub cpu_limit_cores: Option<f32>,
+    /// Path of this process's cgroup, relative to the cgroup2 mount
+    pub cgroup_path: Option<String>,
+}
+
+/// Path of this process's cgroup v2 group from `/proc/self/cgroup`
+///
+/// Only the unified hierarchy (`0::/path`) is understood; a pure v1 system
+/// returns `None`.
+pub fn parse_self_cgroup(contents: &str) -> Option<String> {
+    contents
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(|path| path.trim().to_string())
+}
+
+/// Mount point of the cgroup2 filesystem from `/proc/self/mountinfo`
+pub fn parse_cgroup2_mount(mountinfo: &str) -> Option<PathBuf> {
+    mountinfo.lines().find_map(|line| {
+        // Optional fields end at " - ", followed by the filesystem type
+        let (mount, fs) = line.split_once(" - ")?;
+        if fs.split_whitespace().next()? != "cgroup2" {
+            return None;
+        }
+        mount.split_whitespace().nth(4).map(PathBuf::from)
+    })
+}
+
+/// Directories from `group` up to the mount root, innermost first
+///
+/// A limit set on a parent slice applies to every group below it, so the
+/// effective limit is the smallest one along this chain.
+pub fn cgroup_chain(mount: &Path, group: &str) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let mut current = Path::new(group.trim_start_matches('/'));
+    loop {
+        dirs.push(mount.join(current));
+        match current.parent() {
+            Some(parent) => current = parent,
+            None => break,
+        }
+    }
+    dirs.dedup();
+    dirs
+}
+
+/// Parse `memory.max`; `max` means unlimited
+pub fn parse_memory_max(contents: &str) -> Option<u64> {
+    contents.trim().parse().ok()
+}
+
+/// Parse `cpu.max` (`<quota> <period>`) into a number of cores
+/// `max` as the quota means unlimited
+pub fn parse_cpu_max(contents: &str) -> Option<f32> {
+    let mut fields = contents.split_whitespace();
+    let quota: f64 = fields.next()?.parse().ok()?;
+    let period: f64 = fields.next()?.parse().ok()?;
+
+    (period > 0.0).then(|| (quota / period) as f32)
+}
+
+/// Value of a `key value` line in `memory.stat` or `cpu.stat`
+pub fn stat_value(contents: &str, key: &str) -> Option<u64> {
+    contents.lines().find_map(|line| {
+        let (name, value) = line.split_once(' ')?;
+        (name == key).then(|| value.trim().parse().ok())?
+    })
+}
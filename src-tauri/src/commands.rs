@@ -1,48 +1,128 @@
+use crate::achievements::Achievement;
+use crate::config::Config;
+use crate::illness::Illness;
+use crate::life::Life;
 use crate::monitor::{Metrics, SystemMonitor};
-use crate::pet::{Pet, PetState};
+use crate::needs::Needs;
+use crate::pet::{Assessment, Pet, PetState, StateTransition};
+use crate::progress::Progress;
+use crate::quests::Quests;
+use crate::save::{self, SaveData, SaveError};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::sync::Mutex;
+use std::time::SystemTime;
 use tauri::State;
 
 /// Application state shared across Tauri commands
 pub struct AppState {
     pub monitor: Mutex<SystemMonitor>,
     pub pet: Mutex<Pet>,
+    /// The poller's last sample; `None` until it has taken one
+    pub metrics: Mutex<Option<Metrics>>,
+    /// Where the pet is saved between sessions; `None` if nowhere to keep it
+    /// or the save there could not be loaded
+    pub save_path: Option<PathBuf>,
 }
 
 impl AppState {
+    /// Start with the default config; the poller applies the user's file
+    ///
+    /// The pet is restored from the last session's save file, if any, and
+    /// the time the app was closed is simulated.
     pub fn new() -> Self {
+        Self::with_save_path(save::save_path())
+    }
+
+    /// Like `new`, saving the pet at `save_path` instead of the usual place
+    ///
+    /// A save that is there but cannot be loaded, such as one from a newer
+    /// build, is left alone: the pet starts afresh and is not saved at all
+    /// this session.
+    pub fn with_save_path(mut save_path: Option<PathBuf>) -> Self {
+        let mut monitor = SystemMonitor::new();
+        let mut pet = Pet::new();
+        Config::default().apply(&mut monitor, &mut pet);
+
+        if let Some(path) = &save_path {
+            let (data, problem) = SaveData::load_or_recover(path);
+            if let Some(e) = &problem {
+                eprintln!("Problem with save file {}: {}", path.display(), e);
+            }
+            if problem.as_ref().is_some_and(SaveError::leaves_save_in_place) {
+                eprintln!("Not saving the pet this session, to keep {}", path.display());
+                save_path = None;
+            }
+            if let Some(data) = data {
+                data.restore_into(&mut pet, SystemTime::now());
+            }
+        }
+
         Self {
-            monitor: Mutex::new(SystemMonitor::new()),
-            pet: Mutex::new(Pet::new()),
+            monitor: Mutex::new(monitor),
+            pet: Mutex::new(pet),
+            metrics: Mutex::new(None),
+            save_path,
+        }
+    }
+
+    /// Write the pet to the save file
+    ///
+    /// Must not be called while holding the pet lock.
+    pub fn save(&self) {
+        let Some(path) = &self.save_path else {
+            return;
+        };
+        let data = match self.pet.lock() {
+            Ok(pet) => SaveData::capture(&pet, SystemTime::now()),
+            Err(_) => return,
+        };
+        if let Err(e) = data.save(path) {
+            eprintln!("Failed to save pet: {}", e);
         }
     }
 }
 
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Response containing current system metrics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MetricsResponse {
     pub metrics: Metrics,
     pub pet_state: PetState,
+    /// 0-100, see `Pet::health_score`
+    pub health_score: f32,
     pub pet_emoji: String,
     pub pet_description: String,
 }
 
 /// Tauri command: Get current system metrics and pet state
+///
+/// Returns the poller's last sample rather than updating the pet itself: the
+/// poller announces and saves whatever an update changes, so it must be the
+/// only one making them.
 #[tauri::command]
 pub async fn get_metrics(state: State<'_, AppState>) -> Result<MetricsResponse, String> {
-    // Refresh and get metrics
-    let mut monitor = state.monitor.lock().map_err(|e| e.to_string())?;
-    monitor.refresh();
-    let metrics = monitor.get_metrics();
-
-    // Update pet state
-    let mut pet = state.pet.lock().map_err(|e| e.to_string())?;
-    let pet_state = pet.update(&metrics);
+    let last = state.metrics.lock().map_err(|e| e.to_string())?.clone();
+    let metrics = match last {
+        Some(metrics) => metrics,
+        // Asked before the first poll; sample without touching the pet
+        None => {
+            let mut monitor = state.monitor.lock().map_err(|e| e.to_string())?;
+            monitor.refresh();
+            monitor.get_metrics()
+        }
+    };
 
+    let pet = state.pet.lock().map_err(|e| e.to_string())?;
     Ok(MetricsResponse {
         metrics,
-        pet_state,
+        pet_state: pet.get_state(),
+        health_score: pet.health_score(),
         pet_emoji: pet.get_emoji().to_string(),
         pet_description: pet.get_icon_description().to_string(),
     })
@@ -55,8 +135,11 @@ pub async fn get_pet_state(state: State<'_, AppState>) -> Result<PetStateRespons
 
     Ok(PetStateResponse {
         state: pet.get_state(),
+        health_score: pet.health_score(),
         emoji: pet.get_emoji().to_string(),
         description: pet.get_icon_description().to_string(),
+        last_transition: pet.last_transition().cloned(),
+        life: pet.life().clone(),
     })
 }
 
@@ -64,25 +147,131 @@ pub async fn get_pet_state(state: State<'_, AppState>) -> Result<PetStateRespons
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PetStateResponse {
     pub state: PetState,
+    /// 0-100, see `Pet::health_score`
+    pub health_score: f32,
     pub emoji: String,
     pub description: String,
+    /// Most recent state change and why it happened
+    pub last_transition: Option<StateTransition>,
+    /// Life stage and evolution history
+    pub life: Life,
 }
 
-/// Tauri command: Clean up temporary files
+/// Tauri command: Explain the current pet state metric by metric
 #[tauri::command]
-pub async fn cleanup_temp() -> Result<CleanupResponse, String> {
-    match SystemMonitor::cleanup_temp() {
-        Ok(deleted_mb) => Ok(CleanupResponse {
-            success: true,
-            deleted_mb,
-            message: format!("Successfully cleaned up {} MB of temporary files", deleted_mb),
-        }),
-        Err(e) => Ok(CleanupResponse {
-            success: false,
-            deleted_mb: 0,
-            message: format!("Cleanup encountered errors: {}", e),
-        }),
-    }
+pub async fn explain_state(state: State<'_, AppState>) -> Result<StateExplanation, String> {
+    let pet = state.pet.lock().map_err(|e| e.to_string())?;
+
+    Ok(StateExplanation {
+        state: pet.get_state(),
+        health_score: pet.health_score(),
+        assessments: pet.assessments().to_vec(),
+        last_transition: pet.last_transition().cloned(),
+    })
+}
+
+/// Response explaining why the pet is in its current state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateExplanation {
+    pub state: PetState,
+    pub health_score: f32,
+    /// Every metric's value, levels, status and share of the score
+    pub assessments: Vec<Assessment>,
+    pub last_transition: Option<StateTransition>,
+}
+
+/// Tauri command: Get the pet's needs and overall mood
+#[tauri::command]
+pub async fn get_needs(state: State<'_, AppState>) -> Result<NeedsResponse, String> {
+    let pet = state.pet.lock().map_err(|e| e.to_string())?;
+    let mood = pet.mood();
+
+    Ok(NeedsResponse {
+        needs: pet.needs(),
+        mood,
+        mood_emoji: mood.emoji().to_string(),
+    })
+}
+
+/// Response containing the pet's needs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NeedsResponse {
+    pub needs: Needs,
+    /// Worse of the system state and the needs, see `Pet::mood`
+    pub mood: PetState,
+    pub mood_emoji: String,
+}
+
+/// Tauri command: List every achievement with its unlock time and progress
+#[tauri::command]
+pub async fn list_achievements(state: State<'_, AppState>) -> Result<Vec<Achievement>, String> {
+    let pet = state.pet.lock().map_err(|e| e.to_string())?;
+    Ok(pet.list_achievements())
+}
+
+/// Tauri command: Get the pet's XP, level and streaks
+#[tauri::command]
+pub async fn get_progress(state: State<'_, AppState>) -> Result<ProgressResponse, String> {
+    let pet = state.pet.lock().map_err(|e| e.to_string())?;
+    let progress = pet.progress().clone();
+
+    Ok(ProgressResponse {
+        host: sysinfo::System::host_name(),
+        xp_for_next_level: Progress::xp_for_level(progress.level + 1),
+        progress,
+    })
+}
+
+/// Response containing the pet's progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgressResponse {
+    /// Which machine this pet lives on, to compare pets across boxes
+    pub host: Option<String>,
+    pub progress: Progress,
+    /// Total XP at which the next level is reached
+    pub xp_for_next_level: f64,
+}
+
+/// Tauri command: Get today's quests and how far along they are
+#[tauri::command]
+pub async fn get_quests(state: State<'_, AppState>) -> Result<Quests, String> {
+    let pet = state.pet.lock().map_err(|e| e.to_string())?;
+    Ok(pet.quests().clone())
+}
+
+/// Tauri command: Get whether the pet is sick or dead, and the graveyard of
+/// past pets
+#[tauri::command]
+pub async fn get_illness(state: State<'_, AppState>) -> Result<Illness, String> {
+    let pet = state.pet.lock().map_err(|e| e.to_string())?;
+    Ok(pet.illness().clone())
+}
+
+/// Tauri command: Clean up temporary files, caches and crash dumps, and the
+/// trash if the config allows it
+///
+/// A cleanup that frees space also feeds the pet. Sizing and deleting whole
+/// trees can take a while, so it runs on a blocking thread.
+#[tauri::command]
+pub async fn cleanup_temp(state: State<'_, AppState>) -> Result<CleanupResponse, String> {
+    let options = state
+        .monitor
+        .lock()
+        .map_err(|e| e.to_string())?
+        .cleanup_options();
+    let summary =
+        tauri::async_runtime::spawn_blocking(move || SystemMonitor::cleanup_temp(options))
+            .await
+            .map_err(|e| e.to_string())?;
+
+    let deleted_mb = summary.deleted_mb;
+    state.pet.lock().map_err(|e| e.to_string())?.feed(deleted_mb);
+    state.save();
+    Ok(CleanupResponse {
+        success: true,
+        deleted_mb,
+        message: summary.message(),
+    })
 }
 
 /// Response from cleanup operation
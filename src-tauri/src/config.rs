@@ -0,0 +1,374 @@
+use crate::illness::IllnessRules;
+use crate::junk::CleanupOptions;
+use crate::monitor::SystemMonitor;
+use crate::pet::{EnabledMetrics, Pet, PetThresholds, ScoreWeights, Thresholds};
+use crate::smoothing::{Smoothing, SmoothingConfig};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+
+/// Shortest and longest accepted poll interval, in seconds
+const POLL_INTERVAL_RANGE: (f32, f32) = (1.0, 3600.0);
+
+/// User settings read from `config.toml`
+///
+/// Every key is optional: the file is layered over `Config::default()`, so
+/// a file containing only `[thresholds.cpu.percent] warning = 60` changes
+/// that one level. Unknown keys are rejected so a typo does not silently
+/// fall back to a default.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Seconds between samples
+    pub poll_interval_secs: f32,
+    /// Metrics the pet takes into account
+    pub metrics: EnabledMetrics,
+    pub thresholds: PetThresholds,
+    /// How much each metric counts towards the health score
+    pub weights: ScoreWeights,
+    pub smoothing: SmoothingConfig,
+    /// When the pet falls sick, dies and recovers
+    pub illness: IllnessRules,
+    /// What the cleanup action may delete
+    pub cleanup: CleanupOptions,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            poll_interval_secs: 5.0,
+            metrics: EnabledMetrics::default(),
+            thresholds: PetThresholds::steady(),
+            weights: ScoreWeights::default(),
+            smoothing: Default::default(),
+            illness: IllnessRules::default(),
+            cleanup: CleanupOptions::default(),
+        }
+    }
+}
+
+/// Why a config file could not be used
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("cannot read {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid TOML: {0}")]
+    Parse(String),
+    #[error("`{key}` {problem}")]
+    Invalid { key: String, problem: String },
+}
+
+impl Config {
+    /// Load the config file at `path`; a missing file means all defaults
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(source) => Err(ConfigError::Read {
+                path: path.to_path_buf(),
+                source,
+            }),
+        }
+    }
+
+    /// Parse and validate the contents of a config file
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let user: toml::Table = text
+            .parse()
+            .map_err(|e: toml::de::Error| ConfigError::Parse(e.to_string()))?;
+        let mut merged = toml::Table::try_from(Self::default())
+            .map_err(|e| ConfigError::Parse(e.to_string()))?;
+        merge(&mut merged, user);
+
+        let config: Self = toml::Value::Table(merged)
+            .try_into()
+            .map_err(|e: toml::de::Error| ConfigError::Parse(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check that every value makes sense, naming the first one that does not
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |key: &str, problem: String| {
+            Err(ConfigError::Invalid {
+                key: key.to_string(),
+                problem,
+            })
+        };
+
+        let (min, max) = POLL_INTERVAL_RANGE;
+        if !(min..=max).contains(&self.poll_interval_secs) {
+            return invalid(
+                "poll_interval_secs",
+                format!("must be between {} and {} seconds", min, max),
+            );
+        }
+
+        let t = &self.thresholds;
+        let rising = [
+            ("memory.percent", &t.memory.percent),
+            ("cpu.percent", &t.cpu.percent),
+            ("cpu.load_per_core", &t.cpu.load_per_core),
+            ("cpu.hottest_core_percent", &t.cpu.hottest_core_percent),
+            ("disk.junk_percent", &t.disk.junk_percent),
+            ("disk.used_percent", &t.disk.used_percent),
+            (
+                "network.throughput_mb_per_sec",
+                &t.network.throughput_mb_per_sec,
+            ),
+            ("network.faults_per_sec", &t.network.faults_per_sec),
+            ("io.iowait_percent", &t.io.iowait_percent),
+            ("swap.used_percent", &t.swap.used_percent),
+            ("swap.swap_in_pages_per_sec", &t.swap.swap_in_pages_per_sec),
+            ("pressure.cpu", &t.pressure.cpu),
+            ("pressure.memory", &t.pressure.memory),
+            ("pressure.io", &t.pressure.io),
+            ("thermal.absolute_celsius", &t.thermal.absolute_celsius),
+        ];
+        for (key, levels) in rising {
+            if let Some(problem) = check_levels(levels, true) {
+                return invalid(&format!("thresholds.{}", key), problem);
+            }
+        }
+        // Margins below a sensor's limit shrink as things get worse
+        if let Some(problem) = check_levels(&t.thermal.below_critical_celsius, false) {
+            return invalid("thresholds.thermal.below_critical_celsius", problem);
+        }
+
+        let dwell = &t.dwell;
+        for (key, secs) in [
+            ("thresholds.dwell.happy_secs", dwell.happy_secs),
+            ("thresholds.dwell.okay_secs", dwell.okay_secs),
+            ("thresholds.dwell.stressed_secs", dwell.stressed_secs),
+            ("thresholds.dwell.critical_secs", dwell.critical_secs),
+        ] {
+            if !(secs.is_finite() && secs >= 0.0) {
+                return invalid(
+                    key,
+                    "must be zero or a positive number of seconds".to_string(),
+                );
+            }
+        }
+
+        let w = &self.weights;
+        for (key, weight) in [
+            ("weights.ram", w.ram),
+            ("weights.cpu", w.cpu),
+            ("weights.load", w.load),
+            ("weights.disk_junk", w.disk_junk),
+            ("weights.disk_full", w.disk_full),
+            ("weights.network", w.network),
+            ("weights.iowait", w.iowait),
+            ("weights.swap", w.swap),
+            ("weights.pressure", w.pressure),
+            ("weights.thermal", w.thermal),
+        ] {
+            if !(weight.is_finite() && weight >= 0.0) {
+                return invalid(key, "must be zero or a positive number".to_string());
+            }
+        }
+
+        let s = &self.smoothing;
+        for (key, smoothing) in [
+            ("smoothing.ram_percent", s.ram_percent),
+            ("smoothing.cpu_percent", s.cpu_percent),
+            ("smoothing.hottest_core_percent", s.hottest_core_percent),
+            ("smoothing.iowait_percent", s.iowait_percent),
+            ("smoothing.swap_percent", s.swap_percent),
+            ("smoothing.swap_in_pages_per_sec", s.swap_in_pages_per_sec),
+            ("smoothing.swap_out_pages_per_sec", s.swap_out_pages_per_sec),
+            ("smoothing.disk_junk_percent", s.disk_junk_percent),
+            ("smoothing.load_average", s.load_average),
+            ("smoothing.pressure", s.pressure),
+            ("smoothing.network", s.network),
+            ("smoothing.disk_io", s.disk_io),
+        ] {
+            let problem = match smoothing {
+                Smoothing::Window { secs } if !(secs.is_finite() && secs > 0.0) => {
+                    Some("window `secs` must be a positive number")
+                }
+                Smoothing::Ewma { half_life_secs }
+                    if !(half_life_secs.is_finite() && half_life_secs > 0.0) =>
+                {
+                    Some("`half_life_secs` must be a positive number")
+                }
+                Smoothing::Median { samples: 0 } => Some("median `samples` must be at least 1"),
+                _ => None,
+            };
+            if let Some(problem) = problem {
+                return invalid(key, problem.to_string());
+            }
+        }
+
+        let i = &self.illness;
+        for (key, secs) in [
+            (
+                "illness.sick_after_critical_secs",
+                i.sick_after_critical_secs,
+            ),
+            ("illness.sick_after_neglect_secs", i.sick_after_neglect_secs),
+            ("illness.dead_after_sick_secs", i.dead_after_sick_secs),
+            ("illness.recovery_secs", i.recovery_secs),
+        ] {
+            if !(secs.is_finite() && secs >= 0.0) {
+                return invalid(
+                    key,
+                    "must be zero or a positive number of seconds".to_string(),
+                );
+            }
+        }
+        if !(0.0..=100.0).contains(&i.neglected_below) {
+            return invalid(
+                "illness.neglected_below",
+                "must be between 0 and 100".to_string(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Time between samples
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs_f32(self.poll_interval_secs)
+    }
+
+    /// Apply the settings to a running monitor and pet
+    pub fn apply(&self, monitor: &mut SystemMonitor, pet: &mut Pet) {
+        if monitor.smoothing() != &self.smoothing {
+            monitor.set_smoothing(self.smoothing);
+        }
+        monitor.set_cleanup_options(self.cleanup);
+        pet.set_thresholds(self.thresholds);
+        pet.set_enabled_metrics(self.metrics);
+        pet.set_score_weights(self.weights);
+        pet.set_illness_rules(self.illness);
+    }
+}
+
+/// Problem with one set of levels, if any
+fn check_levels(levels: &Thresholds, rising: bool) -> Option<String> {
+    let values = [
+        levels.elevated_level(),
+        levels.warning,
+        levels.stressed,
+        levels.critical,
+        levels.hysteresis,
+    ];
+    if values.iter().any(|v| !v.is_finite()) {
+        return Some("must only contain finite numbers".to_string());
+    }
+    if levels.hysteresis < 0.0 {
+        return Some("hysteresis must not be negative".to_string());
+    }
+
+    let ordered = if rising {
+        levels.warning <= levels.stressed && levels.stressed <= levels.critical
+    } else {
+        levels.warning >= levels.stressed && levels.stressed >= levels.critical
+    };
+    let elevated_ordered = match levels.elevated {
+        Some(elevated) if rising => elevated <= levels.warning,
+        Some(elevated) => elevated >= levels.warning,
+        None => true,
+    };
+    if !elevated_ordered {
+        return Some(format!(
+            "elevated level must come before warning (got {} and {})",
+            levels.elevated_level(),
+            levels.warning
+        ));
+    }
+    (!ordered).then(|| {
+        format!(
+            "levels must {} from warning to stressed to critical (got {}, {}, {})",
+            if rising { "rise" } else { "fall" },
+            levels.warning,
+            levels.stressed,
+            levels.critical
+        )
+    })
+}
+
+/// Layer `user` over `base`, table by table
+///
+/// A table with a `kind` key is a tagged choice such as a smoothing strategy
+/// and replaces the default outright, so stale fields of the default kind
+/// are not carried over.
+fn merge(base: &mut toml::Table, user: toml::Table) {
+    for (key, value) in user {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(user))
+                if !user.contains_key("kind") =>
+            {
+                merge(base, user)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// `config.toml` in the per-user config directory
+///
+/// `$XDG_CONFIG_HOME/sysadmin-tamagotchi` (falling back to `~/.config`) on
+/// Unix, `%APPDATA%\sysadmin-tamagotchi` on Windows. `None` when none of those
+/// variables is set.
+pub fn config_path() -> Option<PathBuf> {
+    config_path_with(|key| std::env::var(key).ok())
+}
+
+/// `config_path` with an injectable environment lookup
+pub fn config_path_with(env: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    let non_empty = |key: &str| env(key).filter(|v| !v.is_empty()).map(PathBuf::from);
+
+    let dir = if cfg!(windows) {
+        non_empty("APPDATA")
+    } else {
+        non_empty("XDG_CONFIG_HOME").or_else(|| non_empty("HOME").map(|h| h.join(".config")))
+    }?;
+    Some(dir.join("sysadmin-tamagotchi").join("config.toml"))
+}
+
+/// Reloads the config file when it changes
+///
+/// Checked once per poll by comparing modification time and size, which is
+/// a single `stat` and works on every platform.
+pub struct ConfigWatcher {
+    path: PathBuf,
+    /// Modification time and size last seen; `None` while the file is absent
+    seen: Option<(SystemTime, u64)>,
+}
+
+impl ConfigWatcher {
+    /// Watch `path`; the first `poll` reports the file if it exists
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            seen: None,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The reloaded config if the file changed since the last call
+    ///
+    /// Deleting the file reverts to the defaults. An invalid file is
+    /// reported once, and again only after it is edited.
+    pub fn poll(&mut self) -> Option<Result<Config, ConfigError>> {
+        let current = std::fs::metadata(&self.path)
+            .ok()
+            .and_then(|m| Some((m.modified().ok()?, m.len())));
+        if current == self.seen {
+            return None;
+        }
+        self.seen = current;
+        Some(Config::load(&self.path))
+    }
+}
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+
+/// `/proc/diskstats` counts sectors of 512 bytes regardless of the device
+const SECTOR_BYTES: u64 = 512;
+
+/// Throughput and IOPS for one block device, reported in `Metrics`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockDeviceIo {
+    pub device: String,
+    pub read_bytes_per_sec: u64,
+    pub write_bytes_per_sec: u64,
+    pub reads_per_sec: f32,
+    pub writes_per_sec: f32,
+}
+
+impl BlockDeviceIo {
+    /// Completed reads plus writes per second
+    pub fn iops(&self) -> f32 {
+        self.reads_per_sec + self.writes_per_sec
+    }
+}
+
+/// Raw cumulative counters for one block device
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeviceCounters {
+    pub reads: u64,
+    pub sectors_read: u64,
+    pub writes: u64,
+    pub sectors_written: u64,
+}
+
+impl DeviceCounters {
+    fn bytes_read(&self) -> u64 {
+        self.sectors_read * SECTOR_BYTES
+    }
+
+    fn bytes_written(&self) -> u64 {
+        self.sectors_written * SECTOR_BYTES
+    }
+
+    /// Rates between an earlier sample and this one, `secs` apart
+    pub fn io_since(&self, device: &str, prev: &Self, secs: f64) -> BlockDeviceIo {
+        let rate = |now: u64, before: u64| {
+            if secs > 0.0 {
+                now.saturating_sub(before) as f64 / secs
+            } else {
+                0.0
+            }
+        };
+
+        BlockDeviceIo {
+            device: device.to_string(),
+            read_bytes_per_sec: rate(self.bytes_read(), prev.bytes_read()) as u64,
+            write_bytes_per_sec: rate(self.bytes_written(), prev.bytes_written()) as u64,
+            reads_per_sec: rate(self.reads, prev.reads) as f32,
+            writes_per_sec: rate(self.writes, prev.writes) as f32,
+        }
+    }
+}
+
+/// Parse per-device counters from `/proc/diskstats`
+///
+/// Only whole physical or logical devices are kept. Partitions would count
+/// the same I/O twice, and loop, RAM and optical devices say nothing about
+/// storage pressure.
+pub fn parse_diskstats(contents: &str) -> Vec<(String, DeviceCounters)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // major minor name, then at least the 11 classic counters
+            if fields.len() < 14 || !is_whole_device(fields[2]) {
+                return None;
+            }
+
+            let counter = |i: usize| fields[i].parse::<u64>().ok();
+            Some((
+                fields[2].to_string(),
+                DeviceCounters {
+                    reads: counter(3)?,
+                    sectors_read: counter(5)?,
+                    writes: counter(7)?,
+                    sectors_written: counter(9)?,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Whether a kernel block device name is a whole disk worth reporting
+fn is_whole_device(name: &str) -> bool {
+    const IGNORED: [&str; 5] = ["loop", "ram", "zram", "sr", "fd"];
+    if IGNORED.iter().any(|prefix| name.starts_with(prefix)) {
+        return false;
+    }
+
+    // nvme0n1p2, mmcblk0p1: partitions carry a `p<N>` suffix after a digit
+    if name.starts_with("nvme") || name.starts_with("mmcblk") {
+        return !name.rsplit_once('p').is_some_and(|(disk, part)| {
+            disk.ends_with(|c: char| c.is_ascii_digit())
+                && !part.is_empty()
+                && part.chars().all(|c| c.is_ascii_digit())
+        });
+    }
+
+    // sda1, vdb2, xvda1: partitions end in a digit
+    if ["sd", "vd", "hd", "xvd"]
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+    {
+        return !name.ends_with(|c: char| c.is_ascii_digit());
+    }
+
+    // dm-0, md0 and anything else are whole logical devices
+    true
+}
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Capacity of a single mounted filesystem
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiskUsage {
+    pub mount_point: String,
+    pub file_system: String,
+    pub total_mb: u64,
+    pub free_mb: u64,
+    pub used_percent: f32,
+}
+
+impl DiskUsage {
+    pub fn new(
+        mount_point: impl Into<String>,
+        file_system: impl Into<String>,
+        total_mb: u64,
+        free_mb: u64,
+    ) -> Self {
+        let used_percent = if total_mb > 0 {
+            (total_mb.saturating_sub(free_mb) as f32 / total_mb as f32) * 100.0
+        } else {
+            0.0
+        };
+
+        Self {
+            mount_point: mount_point.into(),
+            file_system: file_system.into(),
+            total_mb,
+            free_mb,
+            used_percent,
+        }
+    }
+}
+
+/// List writable, non-empty filesystems known to sysinfo
+///
+/// Read-only mounts (squashfs snaps, ISO images) always report 100% used,
+/// so they are left out rather than permanently alarming the pet.
+pub fn list_disks(disks: &sysinfo::Disks) -> Vec<DiskUsage> {
+    disks
+        .list()
+        .iter()
+        .filter(|disk| !disk.is_read_only() && disk.total_space() > 0)
+        .map(|disk| {
+            DiskUsage::new(
+                disk.mount_point().to_string_lossy(),
+                disk.file_system().to_string_lossy(),
+                disk.total_space() / (1024 * 1024),
+                disk.available_space() / (1024 * 1024),
+            )
+        })
+        .collect()
+}
+
+/// Find the filesystem that holds `path`
+///
+/// Picks the mount point that is the longest component-wise prefix of the
+/// path, so `/home/user/.cache` resolves to `/home` rather than `/` and
+/// `/tmpfoo` does not match a `/tmp` mount.
+pub fn mount_for_path<'a>(disks: &'a [DiskUsage], path: &Path) -> Option<&'a DiskUsage> {
+    disks
+        .iter()
+        .filter(|disk| path.starts_with(&disk.mount_point))
+        .max_by_key(|disk| Path::new(&disk.mount_point).components().count())
+}
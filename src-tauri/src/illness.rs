@@ -0,0 +1,256 @@
+use crate::life::{Life, LifeStage};
+use crate::needs::Needs;
+use crate::pet::PetState;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How the pet itself is doing, beyond what the system's state shows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    #[default]
+    Well,
+    Sick,
+    Dead,
+}
+
+/// What made the pet sick, and killed it if it was not nursed back
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cause {
+    /// The system stayed Critical too long
+    Critical,
+    /// A need stayed neglected too long
+    Neglect,
+}
+
+/// When the pet falls sick, dies and recovers
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IllnessRules {
+    /// Unbroken time Critical that makes the pet sick, in seconds
+    pub sick_after_critical_secs: f32,
+    /// A need below this (0-100) counts as neglected
+    pub neglected_below: f32,
+    /// Unbroken neglect that makes the pet sick, in seconds
+    pub sick_after_neglect_secs: f32,
+    /// Time sick after which the pet dies, in seconds
+    pub dead_after_sick_secs: f32,
+    /// Unbroken time Okay or better with no need neglected that cures a
+    /// sick pet, or lets a new one hatch after a death, in seconds
+    pub recovery_secs: f32,
+}
+
+impl Default for IllnessRules {
+    fn default() -> Self {
+        Self {
+            sick_after_critical_secs: 30.0 * 60.0,
+            neglected_below: 10.0,
+            sick_after_neglect_secs: 6.0 * 3600.0,
+            dead_after_sick_secs: 24.0 * 3600.0,
+            recovery_secs: 10.0 * 60.0,
+        }
+    }
+}
+
+/// One pet that died
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Grave {
+    pub stage: LifeStage,
+    pub level: u32,
+    /// Age at death, in seconds
+    pub lifetime_secs: f64,
+    pub cause: Cause,
+    /// Seconds since the Unix epoch
+    pub died_at_unix_secs: u64,
+}
+
+/// Sickness and past deaths, kept between sessions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Illness {
+    pub condition: Condition,
+    /// Why the pet is sick or died; `None` while well
+    pub cause: Option<Cause>,
+    /// Time spent sick so far, in seconds
+    pub sick_secs: f64,
+    /// Every pet that died, oldest first
+    pub graveyard: Vec<Grave>,
+}
+
+impl Illness {
+    /// The state to show for a pet in this condition on a system in `state`
+    pub fn shown_state(&self, state: PetState) -> PetState {
+        match self.condition {
+            Condition::Well => state,
+            Condition::Sick => PetState::Sick,
+            Condition::Dead => PetState::Dead,
+        }
+    }
+}
+
+/// A change of condition and why it happened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConditionChange {
+    pub from: Condition,
+    pub to: Condition,
+    pub reason: &'static str,
+}
+
+/// Illness plus the clocks it advances on
+#[derive(Debug, Clone, Default)]
+pub struct IllnessTracker {
+    illness: Illness,
+    rules: IllnessRules,
+    last_tick: Option<Instant>,
+    /// Start of the current unbroken Critical stretch
+    critical_since: Option<Instant>,
+    /// Start of the current unbroken neglect
+    neglected_since: Option<Instant>,
+    /// Start of the current unbroken healthy stretch
+    healthy_since: Option<Instant>,
+}
+
+impl IllnessTracker {
+    pub fn new(illness: Illness) -> Self {
+        Self {
+            illness,
+            ..Default::default()
+        }
+    }
+
+    pub fn illness(&self) -> &Illness {
+        &self.illness
+    }
+
+    /// Replace the illness, e.g. with one restored from a save file
+    pub fn set_illness(&mut self, illness: Illness) {
+        self.illness = illness;
+        self.critical_since = None;
+        self.neglected_since = None;
+        self.healthy_since = None;
+    }
+
+    pub fn rules(&self) -> &IllnessRules {
+        &self.rules
+    }
+
+    pub fn set_rules(&mut self, rules: IllnessRules) {
+        self.rules = rules;
+    }
+
+    /// Advance to `now` with the system in `state` and the pet's `needs`
+    ///
+    /// A well pet falls sick after an unbroken stretch Critical or with a
+    /// need neglected; a sick pet dies once it has been sick long enough
+    /// and recovers after an unbroken healthy stretch. After a death the
+    /// same healthy stretch lets a new pet hatch.
+    pub fn update_at(
+        &mut self,
+        state: PetState,
+        needs: Needs,
+        now: Instant,
+    ) -> Option<ConditionChange> {
+        let elapsed = self.last_tick.map_or(0.0, |last| {
+            now.saturating_duration_since(last).as_secs_f64()
+        });
+        self.last_tick = Some(now);
+
+        let neglected = needs.lowest() < self.rules.neglected_below;
+        let since = |start: &mut Option<Instant>, active: bool| -> Duration {
+            match (active, *start) {
+                (false, _) => {
+                    *start = None;
+                    Duration::ZERO
+                }
+                (true, None) => {
+                    *start = Some(now);
+                    Duration::ZERO
+                }
+                (true, Some(start)) => now.saturating_duration_since(start),
+            }
+        };
+        let critical_for = since(&mut self.critical_since, state == PetState::Critical);
+        let neglected_for = since(&mut self.neglected_since, neglected);
+        // A dead pet has no needs to neglect
+        let cared_for = !neglected || self.illness.condition == Condition::Dead;
+        let healthy = state <= PetState::Okay && cared_for;
+        let healthy_for = since(&mut self.healthy_since, healthy);
+        let secs = |secs: f32| Duration::try_from_secs_f32(secs.max(0.0)).unwrap_or(Duration::MAX);
+
+        match self.illness.condition {
+            Condition::Well => {
+                if critical_for >= secs(self.rules.sick_after_critical_secs) {
+                    self.fall_sick(Cause::Critical)
+                } else if neglected_for >= secs(self.rules.sick_after_neglect_secs) {
+                    self.fall_sick(Cause::Neglect)
+                } else {
+                    None
+                }
+            }
+            Condition::Sick => {
+                self.illness.sick_secs += elapsed;
+                if healthy_for >= secs(self.rules.recovery_secs) {
+                    self.change(Condition::Well, "recovered")
+                } else if self.illness.sick_secs >= self.rules.dead_after_sick_secs as f64 {
+                    self.change(Condition::Dead, "sick for too long")
+                } else {
+                    None
+                }
+            }
+            Condition::Dead if healthy_for >= secs(self.rules.recovery_secs) => {
+                self.change(Condition::Well, "a new pet hatched")
+            }
+            Condition::Dead => None,
+        }
+    }
+
+    /// Nurse the pet with a cleanup: a sick pet is cured, and a dead one
+    /// replaced by a new pet
+    pub fn treat(&mut self) -> Option<ConditionChange> {
+        match self.illness.condition {
+            Condition::Well => None,
+            Condition::Sick => self.change(Condition::Well, "treated with a cleanup"),
+            Condition::Dead => self.change(Condition::Well, "a cleanup hatched a new pet"),
+        }
+    }
+
+    /// Add the pet that just died to the graveyard
+    pub fn bury(&mut self, life: &Life, level: u32) {
+        let Some(cause) = self.illness.cause else {
+            return;
+        };
+        let died_at_unix_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        self.illness.graveyard.push(Grave {
+            stage: life.stage,
+            level,
+            lifetime_secs: life.age_secs,
+            cause,
+            died_at_unix_secs,
+        });
+    }
+
+    fn fall_sick(&mut self, cause: Cause) -> Option<ConditionChange> {
+        self.illness.cause = Some(cause);
+        let reason = match cause {
+            Cause::Critical => "critical for too long",
+            Cause::Neglect => "needs neglected for too long",
+        };
+        self.change(Condition::Sick, reason)
+    }
+
+    fn change(&mut self, to: Condition, reason: &'static str) -> Option<ConditionChange> {
+        let from = self.illness.condition;
+        self.illness.condition = to;
+        self.healthy_since = None;
+        if to == Condition::Well {
+            self.illness.cause = None;
+            self.illness.sick_secs = 0.0;
+            self.critical_since = None;
+            self.neglected_since = None;
+        }
+        Some(ConditionChange { from, to, reason })
+    }
+}
@@ -0,0 +1,211 @@
+use crate::scanner::{scan_dir, ScanLimits};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+
+/// Category of a place where disposable files accumulate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JunkKind {
+    /// Temporary directories (`/tmp`, `/var/tmp`, Windows TEMP)
+    Temp,
+    /// Per-user application cache (`$XDG_CACHE_HOME` or `~/.cache`)
+    Cache,
+    /// freedesktop.org trash can
+    Trash,
+    /// Crash dumps written by systemd-coredump or apport
+    CoreDumps,
+}
+
+impl JunkKind {
+    /// Plural description for cleanup messages
+    pub fn description(&self) -> &'static str {
+        match self {
+            JunkKind::Temp => "temporary files",
+            JunkKind::Cache => "caches",
+            JunkKind::Trash => "the trash",
+            JunkKind::CoreDumps => "crash dumps",
+        }
+    }
+}
+
+/// What a cleanup may delete
+///
+/// Every location counts towards disk junk, but emptying the trash is
+/// permanent, so it is only done when asked for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CleanupOptions {
+    /// Also empty the freedesktop.org trash, including whole directories
+    pub empty_trash: bool,
+}
+
+impl CleanupOptions {
+    /// Whether a cleanup may touch locations of `kind`
+    pub fn allows(&self, kind: JunkKind) -> bool {
+        kind != JunkKind::Trash || self.empty_trash
+    }
+}
+
+/// What a cleanup freed, and where
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CleanupSummary {
+    pub deleted_mb: u64,
+    /// Kinds of location cleaned, each once, in the order cleaned
+    pub kinds: Vec<JunkKind>,
+}
+
+impl CleanupSummary {
+    /// E.g. "Freed 12 MB of temporary files, caches and crash dumps"
+    pub fn message(&self) -> String {
+        let kinds: Vec<&str> = self.kinds.iter().map(JunkKind::description).collect();
+        let cleaned = match kinds.split_last() {
+            None => "nothing".to_string(),
+            Some((last, [])) => last.to_string(),
+            Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+        };
+        format!("Freed {} MB of {}", self.deleted_mb, cleaned)
+    }
+}
+
+/// A directory that counts towards disk junk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JunkLocation {
+    pub kind: JunkKind,
+    pub path: PathBuf,
+}
+
+/// Size of a single junk location, reported in `Metrics`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JunkLocationUsage {
+    pub kind: JunkKind,
+    pub path: String,
+    pub size_mb: u64,
+    /// True if the scan hit a limit and `size_mb` is a lower bound
+    pub incomplete: bool,
+    /// True if change notifications keep `size_mb` current between rescans
+    pub watched: bool,
+}
+
+/// Resolve junk locations for this platform from the process environment
+pub fn resolve_junk_locations() -> Vec<JunkLocation> {
+    resolve_junk_locations_with(|key| std::env::var(key).ok())
+}
+
+/// Resolve junk locations using the given environment lookup
+///
+/// Only directories that exist are returned, and each path appears once even
+/// when several variables point at it (e.g. `TMPDIR=/tmp`).
+pub fn resolve_junk_locations_with(env: impl Fn(&str) -> Option<String>) -> Vec<JunkLocation> {
+    let candidates = if cfg!(windows) {
+        windows_candidates(&env)
+    } else {
+        unix_candidates(&env)
+    };
+
+    let mut locations: Vec<JunkLocation> = Vec::new();
+    for (kind, path) in candidates {
+        if path.is_dir() && !locations.iter().any(|l| l.path == path) {
+            locations.push(JunkLocation { kind, path });
+        }
+    }
+
+    locations
+}
+
+/// Windows TEMP directory, falling back to the system TEMP
+fn windows_candidates(env: &impl Fn(&str) -> Option<String>) -> Vec<(JunkKind, PathBuf)> {
+    let temp_dir = env("TEMP")
+        .or_else(|| env("TMP"))
+        .unwrap_or_else(|| "C:\\Windows\\Temp".to_string());
+
+    vec![(JunkKind::Temp, PathBuf::from(temp_dir))]
+}
+
+/// Linux temp, XDG cache, freedesktop trash and core dump directories
+fn unix_candidates(env: &impl Fn(&str) -> Option<String>) -> Vec<(JunkKind, PathBuf)> {
+    let non_empty = |key: &str| env(key).filter(|v| !v.is_empty()).map(PathBuf::from);
+    let home = non_empty("HOME");
+
+    let mut candidates = vec![
+        (
+            JunkKind::Temp,
+            non_empty("TMPDIR").unwrap_or_else(|| PathBuf::from("/tmp")),
+        ),
+        (JunkKind::Temp, PathBuf::from("/tmp")),
+        (JunkKind::Temp, PathBuf::from("/var/tmp")),
+    ];
+
+    if let Some(cache) =
+        non_empty("XDG_CACHE_HOME").or_else(|| home.as_ref().map(|h| h.join(".cache")))
+    {
+        candidates.push((JunkKind::Cache, cache));
+    }
+
+    let data_home =
+        non_empty("XDG_DATA_HOME").or_else(|| home.as_ref().map(|h| h.join(".local/share")));
+    if let Some(data_home) = data_home {
+        candidates.push((JunkKind::Trash, data_home.join("Trash")));
+    }
+
+    candidates.push((
+        JunkKind::CoreDumps,
+        PathBuf::from("/var/lib/systemd/coredump"),
+    ));
+    candidates.push((JunkKind::CoreDumps, PathBuf::from("/var/crash")));
+
+    candidates
+}
+
+/// Remove disposable files from a junk location
+/// Returns the number of bytes freed
+///
+/// Trash is emptied completely (`files/` and `info/`). Other locations only
+/// lose top-level regular files; subdirectories, sockets and hidden files
+/// such as X11 lock files are left alone because running programs own them.
+pub fn clean_location(location: &JunkLocation) -> u64 {
+    match location.kind {
+        JunkKind::Trash => ["files", "info"]
+            .iter()
+            .map(|sub| remove_entries(&location.path.join(sub), true))
+            .sum(),
+        _ => remove_entries(&location.path, false),
+    }
+}
+
+/// Delete entries of a directory, optionally including whole subtrees
+fn remove_entries(dir: &Path, recursive: bool) -> u64 {
+    let mut deleted_bytes = 0u64;
+
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            // symlink_metadata so a link is removed without following it
+            let Ok(metadata) = entry.path().symlink_metadata() else {
+                continue;
+            };
+
+            if metadata.is_file() && (recursive || !hidden) {
+                let size = metadata.len();
+                // Attempt to delete file (ignore errors for locked files)
+                if std::fs::remove_file(entry.path()).is_ok() {
+                    deleted_bytes += size;
+                }
+            } else if metadata.file_type().is_symlink() && recursive {
+                let _ = std::fs::remove_file(entry.path());
+            } else if metadata.is_dir() && recursive {
+                let size = scan_dir(
+                    &entry.path(),
+                    &ScanLimits::default(),
+                    &AtomicBool::new(false),
+                )
+                .bytes;
+                if std::fs::remove_dir_all(entry.path()).is_ok() {
+                    deleted_bytes += size;
+                }
+            }
+        }
+    }
+
+    deleted_bytes
+}
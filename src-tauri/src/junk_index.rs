@@ -0,0 +1,594 @@
+use crate::junk::{JunkLocation, JunkLocationUsage};
+use crate::scanner::{walk_dir, ScanLimits};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Per-file size index for one directory tree
+///
+/// Built by a single bounded walk and then kept current by re-stat'ing the
+/// paths that change notifications point at, so totals stay fresh without
+/// rescanning the whole tree.
+#[derive(Debug, Clone, Default)]
+pub struct SizeIndex {
+    /// Ordered so everything below a directory is one contiguous range
+    files: BTreeMap<PathBuf, u64>,
+    total_bytes: u64,
+    incomplete: bool,
+}
+
+impl SizeIndex {
+    /// Walk `root` and record every regular file
+    /// Returns the index and the directories seen, root included
+    pub fn build(root: &Path, limits: &ScanLimits, cancel: &AtomicBool) -> (Self, Vec<PathBuf>) {
+        Self::build_with(root, limits, cancel, |_| {})
+    }
+
+    /// Like `build`, calling `on_dir` with each directory, root first,
+    /// before any of its entries are read
+    fn build_with(
+        root: &Path,
+        limits: &ScanLimits,
+        cancel: &AtomicBool,
+        mut on_dir: impl FnMut(&Path),
+    ) -> (Self, Vec<PathBuf>) {
+        let mut index = Self::default();
+        let mut dirs = vec![root.to_path_buf()];
+        on_dir(root);
+
+        let scan = walk_dir(root, limits, cancel, |path, metadata| {
+            if metadata.is_dir() {
+                on_dir(path);
+                dirs.push(path.to_path_buf());
+            } else {
+                index.insert(path.to_path_buf(), metadata.len());
+            }
+        });
+        index.incomplete = scan.incomplete;
+
+        (index, dirs)
+    }
+
+    /// Total size of indexed files in bytes
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+
+    /// True if the initial walk hit a limit and the total is a lower bound
+    pub fn is_incomplete(&self) -> bool {
+        self.incomplete
+    }
+
+    /// Re-stat a changed path and update the index
+    /// Returns directories that appeared (e.g. created or moved in)
+    pub fn update_path(
+        &mut self,
+        path: &Path,
+        limits: &ScanLimits,
+        cancel: &AtomicBool,
+    ) -> Vec<PathBuf> {
+        let change = PathChange::read(path, limits, cancel);
+        self.apply(path, change)
+    }
+
+    /// Record what `PathChange::read` found at `path`
+    /// Returns directories that appeared (e.g. created or moved in)
+    fn apply(&mut self, path: &Path, change: PathChange) -> Vec<PathBuf> {
+        match change {
+            PathChange::File(size) => {
+                self.insert(path.to_path_buf(), size);
+                Vec::new()
+            }
+            PathChange::Dir(subtree, dirs) => {
+                // Forget whatever was recorded under this path first
+                self.remove_tree(path);
+                for (file, size) in subtree.files {
+                    self.insert(file, size);
+                }
+                self.incomplete |= subtree.incomplete;
+                dirs
+            }
+            PathChange::Gone => {
+                self.remove_tree(path);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Forget a path and everything below it
+    pub fn remove_tree(&mut self, path: &Path) {
+        if let Some(size) = self.files.remove(path) {
+            self.total_bytes = self.total_bytes.saturating_sub(size);
+            return;
+        }
+
+        // Paths order component by component, so a directory's files follow
+        // it directly
+        let below: Vec<PathBuf> = self
+            .files
+            .range::<Path, _>((Bound::Excluded(path), Bound::Unbounded))
+            .map(|(file, _)| file)
+            .take_while(|file| file.starts_with(path))
+            .cloned()
+            .collect();
+        for file in below {
+            if let Some(size) = self.files.remove(&file) {
+                self.total_bytes = self.total_bytes.saturating_sub(size);
+            }
+        }
+    }
+
+    fn insert(&mut self, path: PathBuf, size: u64) {
+        let previous = self.files.insert(path, size).unwrap_or(0);
+        self.total_bytes = self.total_bytes.saturating_sub(previous) + size;
+    }
+}
+
+/// What a changed path holds now, read without touching any index so a
+/// directory can be walked outside the tracker's lock
+enum PathChange {
+    /// A regular file of this many bytes
+    File(u64),
+    /// A directory's files and the directories below it, itself included
+    Dir(SizeIndex, Vec<PathBuf>),
+    /// Nothing, or nothing worth counting
+    Gone,
+}
+
+impl PathChange {
+    fn read(path: &Path, limits: &ScanLimits, cancel: &AtomicBool) -> Self {
+        match path.symlink_metadata() {
+            Ok(metadata) if metadata.is_file() => PathChange::File(metadata.len()),
+            Ok(metadata) if metadata.is_dir() => {
+                let (subtree, dirs) = SizeIndex::build(path, limits, cancel);
+                PathChange::Dir(subtree, dirs)
+            }
+            _ => PathChange::Gone,
+        }
+    }
+}
+
+/// How junk locations are kept up to date
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackingOptions {
+    /// Bounds for the initial walk and for walks of newly created directories
+    pub limits: ScanLimits,
+    /// How often an unwatched location is rescanned from scratch
+    pub rescan_interval: Duration,
+    /// Use filesystem change notifications where the platform supports them
+    pub watch: bool,
+}
+
+impl Default for TrackingOptions {
+    fn default() -> Self {
+        Self {
+            limits: ScanLimits::default(),
+            rescan_interval: Duration::from_secs(30),
+            watch: true,
+        }
+    }
+}
+
+/// Tracking state of a single junk location
+struct TrackedLocation {
+    location: JunkLocation,
+    index: Option<SizeIndex>,
+    /// True while change notifications cover the whole tree and the index
+    /// is complete, so no rescans are needed
+    watched: bool,
+    last_scan: Option<Instant>,
+    /// Paths changed while a walk of the location is underway; `Some` for
+    /// the length of the walk, then replayed onto the new index
+    pending: Option<Vec<PathBuf>>,
+}
+
+/// State shared between the tracker, its scan worker and the watch thread
+struct Shared {
+    locations: Vec<TrackedLocation>,
+    /// Watch descriptor id -> (location, directory)
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    watch_dirs: HashMap<i32, (usize, PathBuf)>,
+    /// Set once the OS refused more watches; stay on periodic rescans
+    watch_limit_hit: bool,
+}
+
+/// Keeps junk location sizes current with one initial scan per location
+/// followed by inotify updates, falling back to periodic rescans when
+/// notifications are unavailable or the watch limit is reached
+pub struct JunkTracker {
+    options: TrackingOptions,
+    shared: Arc<Mutex<Shared>>,
+    scanning: Arc<AtomicBool>,
+    cancel: Arc<AtomicBool>,
+    /// Handle for adding inotify watches; None when falling back to rescans
+    watches: Option<Watches>,
+}
+
+impl JunkTracker {
+    pub fn new(locations: Vec<JunkLocation>, options: TrackingOptions) -> Self {
+        let shared = Arc::new(Mutex::new(Shared {
+            locations: locations
+                .into_iter()
+                .map(|location| TrackedLocation {
+                    location,
+                    index: None,
+                    watched: false,
+                    last_scan: None,
+                    pending: None,
+                })
+                .collect(),
+            watch_dirs: HashMap::new(),
+            watch_limit_hit: false,
+        }));
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        let watches = if options.watch {
+            start_watcher(Arc::clone(&shared), options.limits, Arc::clone(&cancel))
+        } else {
+            None
+        };
+
+        Self {
+            options,
+            shared,
+            scanning: Arc::new(AtomicBool::new(false)),
+            cancel,
+            watches,
+        }
+    }
+
+    /// Start a background scan of every location that needs one
+    ///
+    /// A location needs a scan before its first reading, and again every
+    /// `rescan_interval` while it is not covered by change notifications or
+    /// its last walk hit a limit.
+    pub fn refresh(&self) {
+        if self.scanning.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let due: Vec<(usize, PathBuf)> = match self.shared.lock() {
+            Ok(shared) => shared
+                .locations
+                .iter()
+                .enumerate()
+                .filter(|(_, tracked)| match tracked.last_scan {
+                    None => true,
+                    Some(at) => !tracked.watched && at.elapsed() >= self.options.rescan_interval,
+                })
+                .map(|(i, tracked)| (i, tracked.location.path.clone()))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+
+        if due.is_empty() {
+            self.scanning.store(false, Ordering::Release);
+            return;
+        }
+
+        let shared = Arc::clone(&self.shared);
+        let scanning = Arc::clone(&self.scanning);
+        let cancel = Arc::clone(&self.cancel);
+        let limits = self.options.limits;
+        let watches = self.watches.clone();
+
+        let spawned = std::thread::Builder::new()
+            .name("junk-scan".to_string())
+            .spawn(move || {
+                for (i, root) in due {
+                    if cancel.load(Ordering::Relaxed) {
+                        break;
+                    }
+
+                    // Each directory is watched before it is read, and what
+                    // changes meanwhile is held back until the index is in
+                    let mut watching = match (&watches, shared.lock()) {
+                        (Some(_), Ok(mut shared)) => {
+                            shared.locations[i].pending = Some(Vec::new());
+                            !shared.locations[i].watched && !shared.watch_limit_hit
+                        }
+                        (None, Ok(_)) => false,
+                        (_, Err(_)) => break,
+                    };
+                    let (index, _) = SizeIndex::build_with(&root, &limits, &cancel, |dir| {
+                        if !watching {
+                            return;
+                        }
+                        watching = match (&watches, shared.lock()) {
+                            (Some(watches), Ok(mut shared)) => {
+                                watch_dirs(&mut shared, watches, i, &[dir.to_path_buf()])
+                            }
+                            _ => false,
+                        };
+                    });
+
+                    {
+                        let Ok(mut shared) = shared.lock() else {
+                            break;
+                        };
+                        // A partial walk leaves the location on periodic rescans
+                        let complete = !index.is_incomplete();
+                        let tracked = &mut shared.locations[i];
+                        tracked.index = Some(index);
+                        tracked.last_scan = Some(Instant::now());
+                        tracked.watched = (tracked.watched || watching) && complete;
+                    }
+
+                    if let Some(watches) = &watches {
+                        replay_pending(&shared, watches, i, &limits, &cancel);
+                    }
+                }
+                scanning.store(false, Ordering::Release);
+            });
+
+        if let Err(e) = spawned {
+            eprintln!("Failed to start junk scan: {}", e);
+            self.scanning.store(false, Ordering::Release);
+        }
+    }
+
+    /// Current per-location sizes; locations not yet scanned are incomplete
+    pub fn usages(&self) -> Vec<JunkLocationUsage> {
+        let Ok(shared) = self.shared.lock() else {
+            return Vec::new();
+        };
+
+        shared
+            .locations
+            .iter()
+            .map(|tracked| JunkLocationUsage {
+                kind: tracked.location.kind,
+                path: tracked.location.path.to_string_lossy().into_owned(),
+                size_mb: tracked.index.as_ref().map_or(0, |i| i.total_bytes()) / (1024 * 1024),
+                incomplete: tracked.index.as_ref().is_none_or(|i| i.is_incomplete()),
+                watched: tracked.watched,
+            })
+            .collect()
+    }
+
+    /// Paths of all tracked locations
+    pub fn locations(&self) -> Vec<JunkLocation> {
+        self.shared
+            .lock()
+            .map(|shared| {
+                shared
+                    .locations
+                    .iter()
+                    .map(|t| t.location.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for JunkTracker {
+    fn drop(&mut self) {
+        // Stop the scan worker and watch thread; nobody will read their results
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(target_os = "linux")]
+use inotify::{EventMask, Inotify, WatchMask, Watches};
+
+/// Without inotify there is nothing to add watches to
+#[cfg(not(target_os = "linux"))]
+type Watches = ();
+
+/// Events that can change the size of a watched tree
+#[cfg(target_os = "linux")]
+const WATCH_MASK: WatchMask = WatchMask::CREATE
+    .union(WatchMask::DELETE)
+    .union(WatchMask::MODIFY)
+    .union(WatchMask::MOVED_FROM)
+    .union(WatchMask::MOVED_TO)
+    .union(WatchMask::ONLYDIR);
+
+/// Start a thread draining inotify events into the shared indexes
+/// Returns a handle for adding watches, or None if inotify is unavailable
+#[cfg(target_os = "linux")]
+fn start_watcher(
+    shared: Arc<Mutex<Shared>>,
+    limits: ScanLimits,
+    cancel: Arc<AtomicBool>,
+) -> Option<Watches> {
+    let mut inotify = match Inotify::init() {
+        Ok(inotify) => inotify,
+        Err(e) => {
+            eprintln!("inotify unavailable, falling back to rescans: {}", e);
+            return None;
+        }
+    };
+    let watches = inotify.watches();
+    let thread_watches = inotify.watches();
+
+    let spawned = std::thread::Builder::new()
+        .name("junk-watch".to_string())
+        .spawn(move || {
+            let mut buffer = [0u8; 4096];
+            while !cancel.load(Ordering::Relaxed) {
+                let events = match inotify.read_events(&mut buffer) {
+                    Ok(events) => events,
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        std::thread::sleep(Duration::from_millis(250));
+                        continue;
+                    }
+                    Err(e) => {
+                        eprintln!("inotify read failed, falling back to rescans: {}", e);
+                        break;
+                    }
+                };
+
+                // Resolve the batch to paths under a short lock
+                let changed: Vec<(usize, PathBuf)> = {
+                    let Ok(mut shared) = shared.lock() else {
+                        break;
+                    };
+                    let mut changed = Vec::new();
+                    for event in events {
+                        if event.mask.contains(EventMask::Q_OVERFLOW) {
+                            // Events were dropped; rescan everything on the next refresh
+                            for tracked in &mut shared.locations {
+                                tracked.last_scan = None;
+                            }
+                            continue;
+                        }
+
+                        let wd = event.wd.get_watch_descriptor_id();
+                        if event.mask.contains(EventMask::IGNORED) {
+                            shared.watch_dirs.remove(&wd);
+                            continue;
+                        }
+
+                        let (Some((i, dir)), Some(name)) = (shared.watch_dirs.get(&wd), event.name)
+                        else {
+                            continue;
+                        };
+                        let (i, path) = (*i, dir.join(name));
+                        // Held back while the location is walked; the walk replays them
+                        match &mut shared.locations[i].pending {
+                            Some(pending) => pending.push(path),
+                            None => changed.push((i, path)),
+                        }
+                    }
+                    changed
+                };
+
+                for (i, path) in changed {
+                    if cancel.load(Ordering::Relaxed)
+                        || !track_change(&shared, &thread_watches, i, &path, &limits, &cancel)
+                    {
+                        break;
+                    }
+                }
+            }
+
+            // Once this thread is gone nothing keeps indexes current
+            if let Ok(mut shared) = shared.lock() {
+                for tracked in &mut shared.locations {
+                    tracked.watched = false;
+                }
+            }
+        });
+
+    match spawned {
+        Ok(_) => Some(watches),
+        Err(e) => {
+            eprintln!("Failed to start junk watcher: {}", e);
+            None
+        }
+    }
+}
+
+/// Bring location `i` up to date with a change at `path`
+///
+/// Walking a new directory can take a while, so it happens unlocked and only
+/// the result is merged under the lock. Returns false if the lock is
+/// poisoned.
+fn track_change(
+    shared: &Mutex<Shared>,
+    watches: &Watches,
+    i: usize,
+    path: &Path,
+    limits: &ScanLimits,
+    cancel: &AtomicBool,
+) -> bool {
+    let change = PathChange::read(path, limits, cancel);
+
+    let Ok(mut shared) = shared.lock() else {
+        return false;
+    };
+    let Some(index) = shared.locations[i].index.as_mut() else {
+        return true;
+    };
+    let new_dirs = index.apply(path, change);
+    let complete = !index.is_incomplete();
+    if !complete || (!new_dirs.is_empty() && !watch_dirs(&mut shared, watches, i, &new_dirs)) {
+        shared.locations[i].watched = false;
+    }
+    true
+}
+
+/// Apply the changes held back while location `i` was walked, until no more
+/// arrive, then let the watch thread apply them directly again
+fn replay_pending(
+    shared: &Mutex<Shared>,
+    watches: &Watches,
+    i: usize,
+    limits: &ScanLimits,
+    cancel: &AtomicBool,
+) {
+    loop {
+        let pending = {
+            let Ok(mut shared) = shared.lock() else {
+                return;
+            };
+            let tracked = &mut shared.locations[i];
+            match tracked.pending.take() {
+                Some(pending) if !pending.is_empty() => {
+                    tracked.pending = Some(Vec::new());
+                    pending
+                }
+                _ => return,
+            }
+        };
+        for path in pending {
+            if cancel.load(Ordering::Relaxed)
+                || !track_change(shared, watches, i, &path, limits, cancel)
+            {
+                return;
+            }
+        }
+    }
+}
+
+/// Add a watch for every directory of a location
+/// Returns false once the OS watch limit is hit
+#[cfg(target_os = "linux")]
+fn watch_dirs(shared: &mut Shared, watches: &Watches, location: usize, dirs: &[PathBuf]) -> bool {
+    let mut watches = watches.clone();
+
+    for dir in dirs {
+        match watches.add(dir, WATCH_MASK) {
+            Ok(wd) => {
+                shared
+                    .watch_dirs
+                    .insert(wd.get_watch_descriptor_id(), (location, dir.clone()));
+            }
+            // inotify reports the per-user watch limit as ENOSPC
+            Err(e) if e.kind() == std::io::ErrorKind::StorageFull => {
+                eprintln!("inotify watch limit reached, falling back to rescans");
+                shared.watch_limit_hit = true;
+                return false;
+            }
+            // Unreadable or vanished directories are simply not covered
+            Err(_) => {}
+        }
+    }
+
+    true
+}
+
+/// Change notifications are only implemented for Linux; elsewhere every
+/// location is kept current by periodic rescans
+#[cfg(not(target_os = "linux"))]
+fn start_watcher(
+    _shared: Arc<Mutex<Shared>>,
+    _limits: ScanLimits,
+    _cancel: Arc<AtomicBool>,
+) -> Option<Watches> {
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+fn watch_dirs(
+    _shared: &mut Shared,
+    _watches: &Watches,
+    _location: usize,
+    _dirs: &[PathBuf],
+) -> bool {
+    false
+}
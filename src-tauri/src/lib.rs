@@ -1,11 +1,98 @@
 // Library module for SysAdmin Tamagotchi
-// Exposes modules for testing
+// Exposes modules for testing and hosts the Tauri entry point
 
+pub mod achievements;
+pub mod cgroup;
 pub mod commands;
+pub mod config;
+pub mod diskio;
+pub mod disks;
+pub mod illness;
+pub mod junk;
+pub mod junk_index;
+pub mod life;
+pub mod load;
 pub mod monitor;
+pub mod needs;
+pub mod network;
 pub mod pet;
 pub mod poller;
+pub mod pressure;
+pub mod processes;
+pub mod progress;
+pub mod quests;
+pub mod save;
+pub mod scanner;
+pub mod smoothing;
+pub mod sources;
+pub mod thermal;
 
 // Re-export key types for testing
-pub use monitor::{Metrics, SystemMonitor};
-pub use pet::{Pet, PetState};
+pub use achievements::{Achievement, AchievementId, AchievementTracker, Achievements};
+pub use cgroup::{EffectiveLimits, LimitSource};
+pub use config::{Config, ConfigError, ConfigWatcher};
+pub use diskio::BlockDeviceIo;
+pub use disks::DiskUsage;
+pub use illness::{
+    Cause, Condition, ConditionChange, Grave, Illness, IllnessRules, IllnessTracker,
+};
+pub use junk::{CleanupOptions, CleanupSummary, JunkKind, JunkLocation, JunkLocationUsage};
+pub use junk_index::{SizeIndex, TrackingOptions};
+pub use life::{Evolution, Life, LifeStage, LifeTracker};
+pub use load::LoadAverage;
+pub use monitor::{Metrics, RawReadings, SystemMonitor};
+pub use needs::{NeedRates, Needs, NeedsTracker};
+pub use network::NetworkUsage;
+pub use pet::{
+    Assessment, CpuThresholds, DiskThresholds, DwellTimes, EnabledMetrics, IoThresholds,
+    MemoryThresholds, MetricStatus, NetworkThresholds, Pet, PetState, PetStats, PetThresholds,
+    PressureThresholds, ScoreWeights, StateTransition, SwapThresholds, ThermalThresholds,
+    Thresholds,
+};
+pub use pressure::{Pressure, PressureStall};
+pub use processes::ProcessUsage;
+pub use progress::{DayRecord, LevelUp, Progress, ProgressTracker};
+pub use quests::{Quest, QuestGoal, QuestTracker, Quests};
+pub use save::{SaveData, SaveError, SAVE_VERSION};
+pub use scanner::{ScanLimits, ScanResult};
+pub use smoothing::{Smoothing, SmoothingConfig};
+pub use sources::{
+    CgroupSource, DiskIoSource, DiskSource, JunkSource, MetricsSource, NetworkSource, ProcSource,
+    ProcessSource, ScriptedSource, SysinfoSource, ThermalSource,
+};
+pub use thermal::TemperatureReading;
+
+use commands::AppState;
+use tauri::Manager;
+
+/// Build and run the Tauri application
+pub fn run() {
+    tauri::Builder::default()
+        .plugin(tauri_plugin_shell::init())
+        // Commands and the poller both reach this through `State<AppState>`
+        .manage(AppState::new())
+        .setup(|app| {
+            // Start background polling
+            poller::start_polling(app.handle().clone());
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            commands::get_metrics,
+            commands::get_pet_state,
+            commands::explain_state,
+            commands::get_needs,
+            commands::list_achievements,
+            commands::get_progress,
+            commands::get_quests,
+            commands::get_illness,
+            commands::cleanup_temp,
+        ])
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            // Save the pet on the way out so the next start picks up here
+            if let tauri::RunEvent::Exit = event {
+                app_handle.state::<AppState>().save();
+            }
+        });
+}
@@ -0,0 +1,178 @@
+use crate::pet::PetState;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Critical episodes resolved within this many seconds count as full care
+const PROMPT_SECS: f64 = 5.0 * 60.0;
+/// Critical episodes lasting this long or more count as no care at all
+const NEGLECT_SECS: f64 = 60.0 * 60.0;
+
+/// Life stages, from youngest to oldest
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifeStage {
+    #[default]
+    Egg,
+    Baby,
+    Teen,
+    Adult,
+    Elder,
+}
+
+impl LifeStage {
+    /// The stage after this one, if any
+    pub fn next(&self) -> Option<LifeStage> {
+        match self {
+            LifeStage::Egg => Some(LifeStage::Baby),
+            LifeStage::Baby => Some(LifeStage::Teen),
+            LifeStage::Teen => Some(LifeStage::Adult),
+            LifeStage::Adult => Some(LifeStage::Elder),
+            LifeStage::Elder => None,
+        }
+    }
+
+    /// Healthy uptime (seconds) and care (0-100) needed to grow out of
+    /// this stage
+    ///
+    /// An egg hatches after 10 healthy minutes whatever happened; later
+    /// stages take hours and need critical states resolved promptly.
+    pub fn requirement(&self) -> Option<(f64, f32)> {
+        match self {
+            LifeStage::Egg => Some((10.0 * 60.0, 0.0)),
+            LifeStage::Baby => Some((2.0 * 3600.0, 50.0)),
+            LifeStage::Teen => Some((12.0 * 3600.0, 60.0)),
+            LifeStage::Adult => Some((72.0 * 3600.0, 70.0)),
+            LifeStage::Elder => None,
+        }
+    }
+}
+
+/// One step from a stage to the next
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Evolution {
+    pub from: LifeStage,
+    pub to: LifeStage,
+    /// Healthy uptime when it happened, in seconds
+    pub healthy_uptime_secs: f64,
+    /// Care when it happened, 0-100
+    pub care: f32,
+}
+
+/// The pet's life so far, kept between sessions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Life {
+    pub stage: LifeStage,
+    /// Time since the egg was laid, including while the app was closed,
+    /// in seconds
+    pub age_secs: f64,
+    /// Time spent Happy or Okay, in seconds
+    pub healthy_uptime_secs: f64,
+    /// Number of critical episodes that have been resolved
+    pub critical_episodes: u32,
+    /// Sum of each resolved episode's care, 0-100 per episode
+    pub episode_care_total: f64,
+    /// Every evolution so far, oldest first
+    pub history: Vec<Evolution>,
+}
+
+impl Life {
+    /// How promptly critical states were resolved, from 0 to 100
+    ///
+    /// Each episode scores 100 if resolved within 5 minutes, falling to 0
+    /// for an hour or more; care is the mean over all episodes, and 100
+    /// while there have been none.
+    pub fn care(&self) -> f32 {
+        if self.critical_episodes == 0 {
+            100.0
+        } else {
+            (self.episode_care_total / self.critical_episodes as f64) as f32
+        }
+    }
+
+    /// Care earned by a critical episode lasting `secs`
+    fn episode_care(secs: f64) -> f64 {
+        let late = (secs - PROMPT_SECS) / (NEGLECT_SECS - PROMPT_SECS);
+        100.0 * (1.0 - late.clamp(0.0, 1.0))
+    }
+}
+
+/// Life plus the clock it advances on
+#[derive(Debug, Clone, Default)]
+pub struct LifeTracker {
+    life: Life,
+    /// When the last update happened and the state the pet was left in
+    last_tick: Option<(Instant, PetState)>,
+    /// Start of the current critical episode
+    critical_since: Option<Instant>,
+}
+
+impl LifeTracker {
+    pub fn new(life: Life) -> Self {
+        Self {
+            life,
+            ..Default::default()
+        }
+    }
+
+    pub fn life(&self) -> &Life {
+        &self.life
+    }
+
+    /// Replace the life, e.g. with one restored from a save file
+    pub fn set_life(&mut self, life: Life) {
+        self.life = life;
+        self.critical_since = None;
+    }
+
+    /// Age the pet by `away` spent with the app closed
+    ///
+    /// Nothing is known about the system's health meanwhile, so only the
+    /// age moves; healthy uptime and care are left as they were.
+    pub fn catch_up(&mut self, away: Duration) {
+        self.life.age_secs += away.as_secs_f64();
+    }
+
+    /// Advance to `now`, the pet being in `state` from now on
+    ///
+    /// The time since the last call counts as healthy if the pet was Happy
+    /// or Okay then. Returns the evolution if the pet grew into a new
+    /// stage; it grows at most one stage per call.
+    pub fn update_at(&mut self, state: PetState, now: Instant) -> Option<Evolution> {
+        if let Some((last, last_state)) = self.last_tick {
+            let elapsed = now.saturating_duration_since(last).as_secs_f64();
+            self.life.age_secs += elapsed;
+            if last_state <= PetState::Okay {
+                self.life.healthy_uptime_secs += elapsed;
+            }
+        }
+        self.last_tick = Some((now, state));
+
+        match (state == PetState::Critical, self.critical_since) {
+            (true, None) => self.critical_since = Some(now),
+            (false, Some(since)) => {
+                let secs = now.saturating_duration_since(since).as_secs_f64();
+                self.life.critical_episodes += 1;
+                self.life.episode_care_total += Life::episode_care(secs);
+                self.critical_since = None;
+            }
+            _ => {}
+        }
+
+        let (uptime, care) = self.life.stage.requirement()?;
+        let next = self.life.stage.next()?;
+        if self.life.healthy_uptime_secs < uptime || self.life.care() < care {
+            return None;
+        }
+
+        let evolution = Evolution {
+            from: self.life.stage,
+            to: next,
+            healthy_uptime_secs: self.life.healthy_uptime_secs,
+            care: self.life.care(),
+        };
+        self.life.stage = next;
+        self.life.history.push(evolution.clone());
+        Some(evolution)
+    }
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// 1, 5 and 15 minute load averages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LoadAverage {
+    pub one: f32,
+    pub five: f32,
+    pub fifteen: f32,
+}
+
+impl LoadAverage {
+    /// Divide by the number of cores, so 1.0 means every core is busy
+    /// regardless of machine size
+    pub fn per_core(&self, cores: usize) -> Option<LoadAverage> {
+        let cores = cores as f32;
+        (cores > 0.0).then(|| LoadAverage {
+            one: self.one / cores,
+            five: self.five / cores,
+            fifteen: self.fifteen / cores,
+        })
+    }
+}
+
+/// Parse the first three fields of `/proc/loadavg`
+pub fn parse_loadavg(contents: &str) -> Option<LoadAverage> {
+    let mut fields = contents.split_whitespace().map(|f| f.parse::<f32>().ok());
+
+    Some(LoadAverage {
+        one: fields.next()??,
+        five: fields.next()??,
+        fifteen: fields.next()??,
+    })
+}
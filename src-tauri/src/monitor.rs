@@ -1,161 +1,344 @@
+use crate::cgroup::EffectiveLimits;
+use crate::diskio::BlockDeviceIo;
+use crate::disks::DiskUsage;
+use crate::junk::{
+    clean_location, resolve_junk_locations, CleanupOptions, CleanupSummary, JunkLocation,
+    JunkLocationUsage,
+};
+use crate::load::LoadAverage;
+use crate::network::NetworkUsage;
+use crate::pressure::{Pressure, PressureStall};
+use crate::processes::ProcessUsage;
+use crate::smoothing::{KeyedSmoother, Smoother, SmoothingConfig};
+use crate::sources::{default_sources, MetricsSource};
+use crate::thermal::TemperatureReading;
 use serde::{Deserialize, Serialize};
-use sysinfo::System;
-use std::collections::VecDeque;
 use std::time::Instant;
 
 /// System metrics snapshot
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Readings are smoothed per `SmoothingConfig`; the unsmoothed values are
+/// kept in `raw` so spikes stay visible.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Metrics {
+    /// Used memory as `total - available`, so reclaimable cache counts as free
     pub ram_percent: f32,
     pub cpu_percent: f32,
+    /// Usage of each logical core, unsmoothed
+    pub per_core_percent: Vec<f32>,
+    /// Busiest single core, in percent
+    pub hottest_core_percent: f32,
+    /// True when one core is pegged while the machine as a whole is not,
+    /// the signature of a single-threaded bottleneck
+    pub single_thread_saturated: bool,
+    /// Whether `ram_percent` and `cpu_percent` are relative to the machine
+    /// or to a cgroup limit
+    pub limits: EffectiveLimits,
+    /// Raw load averages; `None` where the platform has none (Windows)
+    pub load_average: Option<LoadAverage>,
+    /// Load averages divided by the number of cores
+    pub load_per_core: Option<LoadAverage>,
+    /// Share of CPU time spent idle while waiting on I/O
+    pub iowait_percent: f32,
+    pub swap_used_mb: u64,
+    /// Share of swap in use; zero on machines without swap
+    pub swap_percent: f32,
+    pub swap_in_pages_per_sec: f32,
+    pub swap_out_pages_per_sec: f32,
+    /// Linux pressure stall information, where the kernel provides it
+    pub pressure: PressureStall,
     pub disk_junk_percent: f32,
     pub disk_junk_mb: u64,
     pub total_disk_mb: u64,
+    /// Per-location breakdown of `disk_junk_mb`
+    pub junk_locations: Vec<JunkLocationUsage>,
+    /// True while junk sizes are missing or only partially scanned
+    pub junk_scan_incomplete: bool,
+    /// Capacity of every writable mounted filesystem
+    pub disks: Vec<DiskUsage>,
+    /// Processes using the most CPU, busiest first
+    pub top_cpu: Vec<ProcessUsage>,
+    /// Processes with the largest resident memory, largest first
+    pub top_memory: Vec<ProcessUsage>,
+    /// Throughput and faults per network interface, loopback excluded
+    pub networks: Vec<NetworkUsage>,
+    /// Throughput and IOPS per whole block device
+    pub disk_io: Vec<BlockDeviceIo>,
+    /// Every readable temperature sensor; empty when none could be read
+    pub temperatures: Vec<TemperatureReading>,
+    /// Smoothed readings as they were before smoothing
+    pub raw: RawReadings,
 }
 
-/// System monitor that wraps sysinfo and provides clean metrics API
+/// Unsmoothed values of every metric `SmoothingConfig` covers
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RawReadings {
+    pub ram_percent: f32,
+    pub cpu_percent: f32,
+    pub hottest_core_percent: f32,
+    pub iowait_percent: f32,
+    pub swap_percent: f32,
+    pub swap_in_pages_per_sec: f32,
+    pub swap_out_pages_per_sec: f32,
+    pub disk_junk_percent: f32,
+    pub load_average: Option<LoadAverage>,
+    pub load_per_core: Option<LoadAverage>,
+    pub pressure: PressureStall,
+    pub networks: Vec<NetworkUsage>,
+    pub disk_io: Vec<BlockDeviceIo>,
+}
+
+/// Running smoothing state, one per field of `SmoothingConfig`
+struct Smoothers {
+    ram_percent: Smoother,
+    cpu_percent: Smoother,
+    hottest_core_percent: Smoother,
+    iowait_percent: Smoother,
+    swap_percent: Smoother,
+    swap_in_pages_per_sec: Smoother,
+    swap_out_pages_per_sec: Smoother,
+    disk_junk_percent: Smoother,
+    load_average: KeyedSmoother,
+    pressure: KeyedSmoother,
+    network: KeyedSmoother,
+    disk_io: KeyedSmoother,
+}
+
+impl Smoothers {
+    fn new(config: &SmoothingConfig) -> Self {
+        Self {
+            ram_percent: Smoother::new(config.ram_percent),
+            cpu_percent: Smoother::new(config.cpu_percent),
+            hottest_core_percent: Smoother::new(config.hottest_core_percent),
+            iowait_percent: Smoother::new(config.iowait_percent),
+            swap_percent: Smoother::new(config.swap_percent),
+            swap_in_pages_per_sec: Smoother::new(config.swap_in_pages_per_sec),
+            swap_out_pages_per_sec: Smoother::new(config.swap_out_pages_per_sec),
+            disk_junk_percent: Smoother::new(config.disk_junk_percent),
+            load_average: KeyedSmoother::new(config.load_average),
+            pressure: KeyedSmoother::new(config.pressure),
+            network: KeyedSmoother::new(config.network),
+            disk_io: KeyedSmoother::new(config.disk_io),
+        }
+    }
+
+    /// Record the raw readings and replace them with smoothed values
+    fn apply(&mut self, metrics: &mut Metrics, now: Instant) {
+        metrics.raw = RawReadings {
+            ram_percent: metrics.ram_percent,
+            cpu_percent: metrics.cpu_percent,
+            hottest_core_percent: metrics.hottest_core_percent,
+            iowait_percent: metrics.iowait_percent,
+            swap_percent: metrics.swap_percent,
+            swap_in_pages_per_sec: metrics.swap_in_pages_per_sec,
+            swap_out_pages_per_sec: metrics.swap_out_pages_per_sec,
+            disk_junk_percent: metrics.disk_junk_percent,
+            load_average: metrics.load_average,
+            load_per_core: metrics.load_per_core,
+            pressure: metrics.pressure,
+            networks: metrics.networks.clone(),
+            disk_io: metrics.disk_io.clone(),
+        };
+
+        metrics.ram_percent = self.ram_percent.push(now, metrics.ram_percent);
+        metrics.cpu_percent = self.cpu_percent.push(now, metrics.cpu_percent);
+        metrics.hottest_core_percent = self
+            .hottest_core_percent
+            .push(now, metrics.hottest_core_percent);
+        metrics.iowait_percent = self.iowait_percent.push(now, metrics.iowait_percent);
+        metrics.swap_percent = self.swap_percent.push(now, metrics.swap_percent);
+        metrics.swap_in_pages_per_sec = self
+            .swap_in_pages_per_sec
+            .push(now, metrics.swap_in_pages_per_sec);
+        metrics.swap_out_pages_per_sec = self
+            .swap_out_pages_per_sec
+            .push(now, metrics.swap_out_pages_per_sec);
+        metrics.disk_junk_percent = self.disk_junk_percent.push(now, metrics.disk_junk_percent);
+
+        let load = &mut self.load_average;
+        let mut smooth_load = |name: &str, average: &mut Option<LoadAverage>| {
+            if let Some(average) = average {
+                average.one = load.push(&format!("{}.one", name), now, average.one);
+                average.five = load.push(&format!("{}.five", name), now, average.five);
+                average.fifteen = load.push(&format!("{}.fifteen", name), now, average.fifteen);
+            }
+        };
+        smooth_load("raw", &mut metrics.load_average);
+        smooth_load("per_core", &mut metrics.load_per_core);
+        load.end_sample();
+
+        let pressure = &mut self.pressure;
+        let mut smooth_pressure = |name: &str, stall: &mut Option<Pressure>| {
+            if let Some(p) = stall {
+                p.some_avg10 = pressure.push(&format!("{}.some_avg10", name), now, p.some_avg10);
+                p.some_avg60 = pressure.push(&format!("{}.some_avg60", name), now, p.some_avg60);
+                p.full_avg10 = pressure.push(&format!("{}.full_avg10", name), now, p.full_avg10);
+                p.full_avg60 = pressure.push(&format!("{}.full_avg60", name), now, p.full_avg60);
+            }
+        };
+        smooth_pressure("cpu", &mut metrics.pressure.cpu);
+        smooth_pressure("memory", &mut metrics.pressure.memory);
+        smooth_pressure("io", &mut metrics.pressure.io);
+        pressure.end_sample();
+
+        // Byte rates are smoothed as floats and rounded back, so they are
+        // left alone when not smoothed at all
+        if !self.network.is_none() {
+            for n in &mut metrics.networks {
+                let name = n.interface.clone();
+                let mut push = |field: &str, value: f32| {
+                    self.network
+                        .push(&format!("{}.{}", name, field), now, value)
+                };
+                n.rx_bytes_per_sec = push("rx", n.rx_bytes_per_sec as f32).round() as u64;
+                n.tx_bytes_per_sec = push("tx", n.tx_bytes_per_sec as f32).round() as u64;
+                n.faults_per_sec = push("faults", n.faults_per_sec);
+            }
+            self.network.end_sample();
+        }
+        if !self.disk_io.is_none() {
+            for d in &mut metrics.disk_io {
+                let name = d.device.clone();
+                let mut push = |field: &str, value: f32| {
+                    self.disk_io
+                        .push(&format!("{}.{}", name, field), now, value)
+                };
+                d.read_bytes_per_sec = push("read", d.read_bytes_per_sec as f32).round() as u64;
+                d.write_bytes_per_sec = push("write", d.write_bytes_per_sec as f32).round() as u64;
+                d.reads_per_sec = push("reads", d.reads_per_sec);
+                d.writes_per_sec = push("writes", d.writes_per_sec);
+            }
+            self.disk_io.end_sample();
+        }
+    }
+}
+
+/// A core at or above this usage counts as pegged
+const PEGGED_CORE_PERCENT: f32 = 95.0;
+
+/// Overall usage below which a pegged core means a single-thread bottleneck
+const SATURATION_MAX_AVERAGE_PERCENT: f32 = 50.0;
+
+/// System monitor that composes metrics sources into a clean metrics API
 pub struct SystemMonitor {
-    sys: System,
+    sources: Vec<Box<dyn MetricsSource>>,
     last_update: Instant,
-    // CPU smoothing buffer - stores last 30 seconds of readings (6 samples at 5s interval)
-    cpu_history: VecDeque<f32>,
-    max_history_size: usize,
+    smoothing: SmoothingConfig,
+    smoothers: Smoothers,
+    cleanup: CleanupOptions,
 }
 
 impl SystemMonitor {
-    /// Create a new system monitor
+    /// Create a new system monitor with the platform's default sources
     pub fn new() -> Self {
-        let mut sys = System::new_all();
-        sys.refresh_all();
+        Self::with_sources(default_sources())
+    }
 
+    /// Create a system monitor from an explicit list of sources
+    /// Sources are collected in order; later sources overwrite earlier fields
+    pub fn with_sources(sources: Vec<Box<dyn MetricsSource>>) -> Self {
         Self {
-            sys,
+            sources,
             last_update: Instant::now(),
-            cpu_history: VecDeque::with_capacity(6), // 30 seconds / 5 seconds = 6 samples
-            max_history_size: 6,
+            smoothing: SmoothingConfig::default(),
+            smoothers: Smoothers::new(&SmoothingConfig::default()),
+            cleanup: CleanupOptions::default(),
         }
     }
 
-    /// Refresh system information (call before get_metrics)
-    pub fn refresh(&mut self) {
-        self.sys.refresh_memory();
-        self.sys.refresh_cpu_all();
-        self.last_update = Instant::now();
+    /// Current smoothing configuration
+    pub fn smoothing(&self) -> &SmoothingConfig {
+        &self.smoothing
     }
 
-    /// Get current system metrics with temporal smoothing for CPU
-    pub fn get_metrics(&mut self) -> Metrics {
-        let ram_percent = self.get_ram_percent();
-        let raw_cpu = self.get_cpu_percent();
+    /// Replace the smoothing configuration, discarding accumulated history
+    pub fn set_smoothing(&mut self, config: SmoothingConfig) {
+        self.smoothers = Smoothers::new(&config);
+        self.smoothing = config;
+    }
 
-        // Add to CPU history buffer
-        self.cpu_history.push_back(raw_cpu);
-        if self.cpu_history.len() > self.max_history_size {
-            self.cpu_history.pop_front();
-        }
+    /// What `cleanup_temp` may delete
+    pub fn cleanup_options(&self) -> CleanupOptions {
+        self.cleanup
+    }
 
-        // Calculate smoothed CPU (average of last 30 seconds)
-        let cpu_percent = if !self.cpu_history.is_empty() {
-            self.cpu_history.iter().sum::<f32>() / self.cpu_history.len() as f32
-        } else {
-            raw_cpu
-        };
+    pub fn set_cleanup_options(&mut self, options: CleanupOptions) {
+        self.cleanup = options;
+    }
 
-        let (disk_junk_mb, total_disk_mb) = self.get_disk_junk();
-        let disk_junk_percent = if total_disk_mb > 0 {
-            (disk_junk_mb as f32 / total_disk_mb as f32) * 100.0
-        } else {
-            0.0
-        };
+    /// Register an additional source after the existing ones
+    pub fn add_source(&mut self, source: Box<dyn MetricsSource>) {
+        self.sources.push(source);
+    }
 
-        Metrics {
-            ram_percent,
-            cpu_percent,
-            disk_junk_percent,
-            disk_junk_mb,
-            total_disk_mb,
+    /// Refresh system information (call before get_metrics)
+    pub fn refresh(&mut self) {
+        for source in &mut self.sources {
+            source.refresh();
         }
+        self.last_update = Instant::now();
     }
 
-    /// Get RAM usage as percentage (0-100)
-    fn get_ram_percent(&self) -> f32 {
-        let total = self.sys.total_memory() as f64;
-        let used = self.sys.used_memory() as f64;
+    /// Get current system metrics, smoothed per the smoothing configuration
+    pub fn get_metrics(&mut self) -> Metrics {
+        self.get_metrics_at(Instant::now())
+    }
 
-        if total > 0.0 {
-            ((used / total) * 100.0) as f32
-        } else {
-            0.0
+    /// Get metrics as if sampled at `now`
+    ///
+    /// Time-based smoothing uses the real interval between calls rather than
+    /// assuming a fixed poll rate; this lets tests control that interval.
+    pub fn get_metrics_at(&mut self, now: Instant) -> Metrics {
+        let mut metrics = Metrics::default();
+        for source in &mut self.sources {
+            source.collect(&mut metrics);
         }
-    }
 
-    /// Get CPU usage as percentage (0-100)
-    fn get_cpu_percent(&self) -> f32 {
-        self.sys.global_cpu_usage()
-    }
+        // Derived readings are computed from raw values, before smoothing
+        metrics.hottest_core_percent = metrics.per_core_percent.iter().copied().fold(0.0, f32::max);
+        metrics.single_thread_saturated = metrics.hottest_core_percent >= PEGGED_CORE_PERCENT
+            && metrics.cpu_percent < SATURATION_MAX_AVERAGE_PERCENT;
 
-    /// Get disk junk size in MB and total disk size (scans Windows TEMP folder)
-    /// Returns (junk_mb, total_disk_mb)
-    fn get_disk_junk(&self) -> (u64, u64) {
-        // Get Windows TEMP directory
-        let temp_dir = std::env::var("TEMP")
-            .or_else(|_| std::env::var("TMP"))
-            .unwrap_or_else(|_| "C:\\Windows\\Temp".to_string());
-
-        // Get total disk size for the drive containing TEMP
-        let total_disk_mb = self.get_total_disk_size(&temp_dir);
-
-        // Calculate total size of files in TEMP
-        let junk_bytes = match calculate_dir_size(&temp_dir) {
-            Ok(bytes) => bytes,
-            Err(_) => 0,
+        metrics.disk_junk_percent = if metrics.total_disk_mb > 0 {
+            (metrics.disk_junk_mb as f32 / metrics.total_disk_mb as f32) * 100.0
+        } else {
+            0.0
         };
 
-        let junk_mb = junk_bytes / (1024 * 1024);
+        self.smoothers.apply(&mut metrics, now);
 
-        (junk_mb, total_disk_mb)
+        metrics
     }
 
-    /// Get total disk size in MB for the drive containing the given path
-    fn get_total_disk_size(&self, _path: &str) -> u64 {
-        // Get disk information from sysinfo
-        let disks = sysinfo::Disks::new_with_refreshed_list();
-
-        // For simplicity, get the C: drive total space
-        // In production, we'd parse the path to determine which disk
-        for disk in disks.list() {
-            if disk.mount_point().to_string_lossy().starts_with("C:") {
-                return disk.total_space() / (1024 * 1024); // Convert to MB
+    /// Clean up junk files in every location resolved for this platform
+    /// that `options` allows
+    ///
+    /// Files that cannot be deleted are skipped rather than failing the
+    /// cleanup, so it always reports what it did free.
+    pub fn cleanup_temp(options: CleanupOptions) -> CleanupSummary {
+        let locations: Vec<JunkLocation> = resolve_junk_locations()
+            .into_iter()
+            .filter(|location| options.allows(location.kind))
+            .collect();
+        let mut kinds = Vec::new();
+        for location in &locations {
+            if !kinds.contains(&location.kind) {
+                kinds.push(location.kind);
             }
         }
 
-        // Default fallback: 256GB
-        256 * 1024
+        CleanupSummary {
+            deleted_mb: Self::cleanup_locations(&locations),
+            kinds,
+        }
     }
 
-    /// Clean up temp files (Windows TEMP directory)
-    pub fn cleanup_temp() -> Result<u64, std::io::Error> {
-        let temp_dir = std::env::var("TEMP")
-            .or_else(|_| std::env::var("TMP"))
-            .unwrap_or_else(|_| "C:\\Windows\\Temp".to_string());
-
-        let mut deleted_bytes = 0u64;
-
-        if let Ok(entries) = std::fs::read_dir(&temp_dir) {
-            for entry in entries.flatten() {
-                if let Ok(metadata) = entry.metadata() {
-                    if metadata.is_file() {
-                        let size = metadata.len();
-                        // Attempt to delete file (ignore errors for locked files)
-                        if std::fs::remove_file(entry.path()).is_ok() {
-                            deleted_bytes += size;
-                        }
-                    }
-                }
-            }
-        }
+    /// Clean up junk files in the given locations
+    /// Returns MB deleted
+    pub fn cleanup_locations(locations: &[JunkLocation]) -> u64 {
+        let deleted_bytes: u64 = locations.iter().map(clean_location).sum();
 
-        Ok(deleted_bytes / (1024 * 1024)) // Return MB deleted
+        deleted_bytes / (1024 * 1024)
     }
 }
 
@@ -164,28 +347,3 @@ impl Default for SystemMonitor {
         Self::new()
     }
 }
-
-/// Calculate total size of a directory in bytes
-fn calculate_dir_size(path: &str) -> std::io::Result<u64> {
-    let mut total_size = 0u64;
-
-    // Use a simple recursive calculation with basic error handling
-    if let Ok(entries) = std::fs::read_dir(path) {
-        for entry in entries.flatten() {
-            if let Ok(metadata) = entry.metadata() {
-                if metadata.is_file() {
-                    total_size += metadata.len();
-                } else if metadata.is_dir() {
-                    // Recursively calculate subdirectories
-                    // Note: This is a simplified version. Could add depth limit
-                    // or timeout for production use to avoid hanging on large dirs
-                    if let Some(path_str) = entry.path().to_str().map(String::from) {
-                        total_size += calculate_dir_size(&path_str).unwrap_or(0);
-                    }
-                }
-            }
-        }
-    }
-
-    Ok(total_size)
-}
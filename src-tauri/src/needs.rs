@@ -0,0 +1,197 @@
+use crate::monitor::Metrics;
+use crate::pet::PetState;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Highest value of a need, when it is fully met
+pub const NEED_MAX: f32 = 100.0;
+
+/// What the pet needs besides a healthy system, each from 0 (neglected)
+/// to 100 (fully met)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Needs {
+    /// Fullness; drops over time and is topped up by running a cleanup
+    pub hunger: f32,
+    /// Drains over time and recovers while the system stays quiet
+    pub energy: f32,
+    /// Drops as junk piles up and recovers as it is cleared
+    pub hygiene: f32,
+}
+
+impl Default for Needs {
+    fn default() -> Self {
+        Self {
+            hunger: NEED_MAX,
+            energy: NEED_MAX,
+            hygiene: NEED_MAX,
+        }
+    }
+}
+
+impl Needs {
+    /// The need closest to empty
+    pub fn lowest(&self) -> f32 {
+        self.hunger.min(self.energy).min(self.hygiene)
+    }
+
+    /// How the pet feels about its needs alone, banded like the health score
+    pub fn state(&self) -> PetState {
+        PetState::from_score(self.lowest())
+    }
+
+    fn clamp(self) -> Self {
+        let clamp = |v: f32| {
+            if v.is_finite() {
+                v.clamp(0.0, NEED_MAX)
+            } else {
+                NEED_MAX
+            }
+        };
+        Self {
+            hunger: clamp(self.hunger),
+            energy: clamp(self.energy),
+            hygiene: clamp(self.hygiene),
+        }
+    }
+}
+
+/// How fast needs change
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NeedRates {
+    /// Hunger lost per hour
+    pub hunger_per_hour: f32,
+    /// Hunger restored by a cleanup that freed any space
+    pub meal: f32,
+    /// Energy lost per hour while the system is busy
+    pub energy_per_hour: f32,
+    /// Energy regained per hour while resting
+    pub rest_energy_per_hour: f32,
+    /// CPU usage below which the system counts as quiet
+    pub rest_below_cpu_percent: f32,
+    /// How long the system must stay quiet before the pet rests
+    pub rest_after_secs: f32,
+    /// Hygiene lost per GB of junk growth, and regained per GB cleared
+    pub hygiene_per_junk_gb: f32,
+}
+
+impl Default for NeedRates {
+    /// Hungry after about a day, tired after a busy working day
+    fn default() -> Self {
+        Self {
+            hunger_per_hour: 4.0,
+            meal: 30.0,
+            energy_per_hour: 6.0,
+            rest_energy_per_hour: 20.0,
+            rest_below_cpu_percent: 30.0,
+            rest_after_secs: 300.0,
+            hygiene_per_junk_gb: 10.0,
+        }
+    }
+}
+
+/// Needs plus the clock and readings they change against
+#[derive(Debug, Clone)]
+pub struct NeedsTracker {
+    needs: Needs,
+    rates: NeedRates,
+    /// When the needs were last advanced
+    last_tick: Option<Instant>,
+    last_junk_mb: Option<u64>,
+    /// Start of the current quiet stretch, if the system is quiet
+    quiet_since: Option<Instant>,
+}
+
+impl Default for NeedsTracker {
+    fn default() -> Self {
+        Self::new(Needs::default())
+    }
+}
+
+impl NeedsTracker {
+    pub fn new(needs: Needs) -> Self {
+        Self {
+            needs: needs.clamp(),
+            rates: NeedRates::default(),
+            last_tick: None,
+            last_junk_mb: None,
+            quiet_since: None,
+        }
+    }
+
+    pub fn needs(&self) -> Needs {
+        self.needs
+    }
+
+    /// Replace the needs, e.g. with ones restored from a save file
+    pub fn set_needs(&mut self, needs: Needs) {
+        self.needs = needs.clamp();
+    }
+
+    pub fn rates(&self) -> &NeedRates {
+        &self.rates
+    }
+
+    pub fn set_rates(&mut self, rates: NeedRates) {
+        self.rates = rates;
+    }
+
+    /// Advance the needs to `now` given the latest metrics
+    ///
+    /// The first call only starts the clock. Junk growth is measured
+    /// between complete junk readings, so a cleanup that frees junk also
+    /// restores hygiene, while a scan still running (which undercounts)
+    /// is neither compared nor remembered.
+    pub fn update_at(&mut self, metrics: &Metrics, now: Instant) {
+        let hours = self.last_tick.map_or(0.0, |last| {
+            now.saturating_duration_since(last).as_secs_f32() / 3600.0
+        });
+        self.last_tick = Some(now);
+
+        let rates = self.rates;
+        let mut needs = self.needs;
+        needs.hunger -= rates.hunger_per_hour * hours;
+
+        if metrics.cpu_percent < rates.rest_below_cpu_percent {
+            self.quiet_since.get_or_insert(now);
+        } else {
+            self.quiet_since = None;
+        }
+        let resting = self.quiet_since.is_some_and(|since| {
+            now.saturating_duration_since(since).as_secs_f32() >= rates.rest_after_secs
+        });
+        needs.energy += if resting {
+            rates.rest_energy_per_hour * hours
+        } else {
+            -rates.energy_per_hour * hours
+        };
+
+        if !metrics.junk_scan_incomplete {
+            if let Some(last_junk_mb) = self.last_junk_mb {
+                let grown_gb = (metrics.disk_junk_mb as f32 - last_junk_mb as f32) / 1024.0;
+                needs.hygiene -= rates.hygiene_per_junk_gb * grown_gb;
+            }
+            self.last_junk_mb = Some(metrics.disk_junk_mb);
+        }
+
+        self.needs = needs.clamp();
+    }
+
+    /// Account for `away` spent with the app closed
+    ///
+    /// Hunger keeps dropping; the pet is assumed to have slept, so energy
+    /// recovers. Hygiene is left for the next junk reading to settle.
+    pub fn catch_up(&mut self, away: Duration) {
+        let hours = away.as_secs_f32() / 3600.0;
+        self.needs.hunger -= self.rates.hunger_per_hour * hours;
+        self.needs.energy += self.rates.rest_energy_per_hour * hours;
+        self.needs = self.needs.clamp();
+    }
+
+    /// Feed the pet after a cleanup that freed `deleted_mb`
+    pub fn feed(&mut self, deleted_mb: u64) {
+        if deleted_mb > 0 {
+            self.needs.hunger += self.rates.meal;
+            self.needs = self.needs.clamp();
+        }
+    }
+}
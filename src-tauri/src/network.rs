@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+/// Throughput and fault readings for one network interface, reported in `Metrics`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkUsage {
+    pub interface: String,
+    pub rx_bytes_per_sec: u64,
+    pub tx_bytes_per_sec: u64,
+    /// Cumulative counters since the interface came up
+    pub rx_errors: u64,
+    pub tx_errors: u64,
+    pub rx_dropped: u64,
+    pub tx_dropped: u64,
+    /// Errors plus drops per second over the last sample
+    pub faults_per_sec: f32,
+}
+
+impl NetworkUsage {
+    /// Rate of the busier direction in MB/s
+    pub fn peak_mb_per_sec(&self) -> f32 {
+        self.rx_bytes_per_sec.max(self.tx_bytes_per_sec) as f32 / (1024.0 * 1024.0)
+    }
+}
+
+/// Raw cumulative counters for one interface
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InterfaceCounters {
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub rx_errors: u64,
+    pub tx_errors: u64,
+    pub rx_dropped: u64,
+    pub tx_dropped: u64,
+}
+
+impl InterfaceCounters {
+    fn faults(&self) -> u64 {
+        self.rx_errors + self.tx_errors + self.rx_dropped + self.tx_dropped
+    }
+
+    /// Rates between an earlier sample and this one, `secs` apart
+    ///
+    /// Counters that went backwards (interface reset, driver reload) are
+    /// treated as zero rather than as a huge wrapped delta.
+    pub fn usage_since(&self, interface: &str, prev: &Self, secs: f64) -> NetworkUsage {
+        let rate = |now: u64, before: u64| {
+            if secs > 0.0 {
+                now.saturating_sub(before) as f64 / secs
+            } else {
+                0.0
+            }
+        };
+
+        NetworkUsage {
+            interface: interface.to_string(),
+            rx_bytes_per_sec: rate(self.rx_bytes, prev.rx_bytes) as u64,
+            tx_bytes_per_sec: rate(self.tx_bytes, prev.tx_bytes) as u64,
+            rx_errors: self.rx_errors,
+            tx_errors: self.tx_errors,
+            rx_dropped: self.rx_dropped,
+            tx_dropped: self.tx_dropped,
+            faults_per_sec: rate(self.faults(), prev.faults()) as f32,
+        }
+    }
+}
+
+/// Parse per-interface counters from `/proc/net/dev`
+///
+/// The loopback interface is skipped: its traffic never leaves the machine.
+pub fn parse_net_dev(contents: &str) -> Vec<(String, InterfaceCounters)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (name, fields) = line.split_once(':')?;
+            let name = name.trim();
+            let fields: Vec<u64> = fields
+                .split_whitespace()
+                .filter_map(|f| f.parse().ok())
+                .collect();
+
+            // 8 receive columns followed by 8 transmit columns
+            if name == "lo" || fields.len() < 16 {
+                return None;
+            }
+
+            Some((
+                name.to_string(),
+                InterfaceCounters {
+                    rx_bytes: fields[0],
+                    rx_errors: fields[2],
+                    rx_dropped: fields[3],
+                    tx_bytes: fields[8],
+                    tx_errors: fields[10],
+                    tx_dropped: fields[11],
+                },
+            ))
+        })
+        .collect()
+}
@@ -1,143 +1,1270 @@
+use crate::achievements::{Achievement, AchievementTracker, Achievements};
+use crate::illness::{Condition, ConditionChange, Illness, IllnessRules, IllnessTracker};
+use crate::life::{Evolution, Life, LifeStage, LifeTracker};
 use crate::monitor::Metrics;
+use crate::needs::{NeedRates, Needs, NeedsTracker};
+use crate::pressure::Pressure;
+use crate::progress::{Progress, ProgressTracker};
+use crate::quests::{QuestTracker, Quests};
+use crate::thermal::TemperatureReading;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
-/// Pet state based on system health
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Number of elevated metrics that together make the pet merely Okay
+const ELEVATED_METRICS_FOR_OKAY: usize = 3;
+
+/// Lowest health score of the Happy, Okay and Stressed bands (exclusive)
+const HAPPY_ABOVE: f32 = 75.0;
+const OKAY_ABOVE: f32 = 50.0;
+const STRESSED_ABOVE: f32 = 25.0;
+
+/// Pet state based on system health, from best to worst
+///
+/// The first four follow the metrics. Sick and Dead follow the pet's
+/// `Illness` and take precedence over them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum PetState {
-    /// All metrics are good
+    /// All metrics are good, or at most a couple elevated
     Happy,
-    /// One metric is in warning range
+    /// One metric is in warning range, or several are elevated
     Okay,
-    /// Multiple metrics in warning or one in critical
+    /// One metric is stressed, or multiple are in warning
     Stressed,
     /// Any metric is critical
     Critical,
+    /// Left Critical or neglected too long; see `IllnessRules`
+    Sick,
+    /// Left sick too long, until a cleanup or a recovery brings a new pet
+    Dead,
 }
 
 impl PetState {
+    /// Overall state from the status of every judged metric
+    ///
+    /// Aggregation policy, first matching rule wins:
+    /// - any metric Critical: Critical
+    /// - any metric Stressed, or two or more at Warning: Stressed
+    /// - one metric at Warning, or three or more Elevated: Okay
+    /// - otherwise: Happy
+    ///
+    /// Each rule counts metrics at that level or worse, so a Stressed and a
+    /// Warning metric together are still Stressed, not Critical.
+    pub fn from_statuses(statuses: &[MetricStatus]) -> PetState {
+        let at_least = |level: MetricStatus| statuses.iter().filter(|&&s| s >= level).count();
+
+        if at_least(MetricStatus::Critical) > 0 {
+            PetState::Critical
+        } else if at_least(MetricStatus::Stressed) > 0 || at_least(MetricStatus::Warning) >= 2 {
+            PetState::Stressed
+        } else if at_least(MetricStatus::Warning) == 1
+            || at_least(MetricStatus::Elevated) >= ELEVATED_METRICS_FOR_OKAY
+        {
+            PetState::Okay
+        } else {
+            PetState::Happy
+        }
+    }
+
+    /// State for a 0-100 health score
+    ///
+    /// Bands: Happy above 75, Okay above 50, Stressed above 25, Critical
+    /// at 25 or below.
+    pub fn from_score(score: f32) -> PetState {
+        if score > HAPPY_ABOVE {
+            PetState::Happy
+        } else if score > OKAY_ABOVE {
+            PetState::Okay
+        } else if score > STRESSED_ABOVE {
+            PetState::Stressed
+        } else {
+            PetState::Critical
+        }
+    }
+
+    /// Highest health score within this state's band
+    pub fn max_score(&self) -> f32 {
+        match self {
+            PetState::Happy => 100.0,
+            PetState::Okay => HAPPY_ABOVE,
+            PetState::Stressed => OKAY_ABOVE,
+            // Not score bands of their own
+            PetState::Critical | PetState::Sick | PetState::Dead => STRESSED_ABOVE,
+        }
+    }
+
     /// Get emoji representation of pet state
-    pub fn emoji(&self) -> &str {
+    pub fn emoji(&self) -> &'static str {
         match self {
             PetState::Happy => "😊",
             PetState::Okay => "😐",
             PetState::Stressed => "😰",
             PetState::Critical => "🔥",
+            PetState::Sick => "🤒",
+            PetState::Dead => "💀",
         }
     }
 
     /// Get description of pet state
-    pub fn description(&self) -> &str {
+    pub fn description(&self) -> &'static str {
         match self {
             PetState::Happy => "Pet is happy - system healthy!",
             PetState::Okay => "Pet is okay - minor resource usage",
             PetState::Stressed => "Pet is stressed - high resource usage",
             PetState::Critical => "Pet is critical - system overloaded!",
+            PetState::Sick => "Pet is sick - run a cleanup or let the system recover",
+            PetState::Dead => "Pet has died - a cleanup or a recovery brings a new one",
+        }
+    }
+}
+
+/// How worrying one metric's reading is, from best to worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricStatus {
+    /// Below the elevated level
+    Good,
+    /// Worth watching, but not a problem on its own
+    Elevated,
+    Warning,
+    Stressed,
+    Critical,
+}
+
+impl MetricStatus {
+    fn label(&self) -> &'static str {
+        match self {
+            MetricStatus::Good => "good",
+            MetricStatus::Elevated => "elevated",
+            MetricStatus::Warning => "warning",
+            MetricStatus::Stressed => "stressed",
+            MetricStatus::Critical => "critical",
+        }
+    }
+}
+
+/// Share of the warning level at which a metric counts as elevated,
+/// unless `Thresholds::elevated` is set
+const DEFAULT_ELEVATED_FRACTION: f32 = 0.8;
+
+/// Elevated, warning, stressed and critical levels for one metric
+///
+/// A level is entered when the reading reaches it and left only once the
+/// reading drops below the level minus `hysteresis`, so a value hovering
+/// around a boundary does not flip the pet back and forth.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Thresholds {
+    /// Defaults to 80% of `warning`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elevated: Option<f32>,
+    pub warning: f32,
+    pub stressed: f32,
+    pub critical: f32,
+    /// Gap between the enter and exit point of each level, in the metric's unit
+    #[serde(default)]
+    pub hysteresis: f32,
+}
+
+impl Thresholds {
+    pub const fn new(warning: f32, stressed: f32, critical: f32) -> Self {
+        Self {
+            elevated: None,
+            warning,
+            stressed,
+            critical,
+            hysteresis: 0.0,
+        }
+    }
+
+    /// Same levels with an explicit elevated level
+    pub const fn with_elevated(self, elevated: f32) -> Self {
+        Self {
+            elevated: Some(elevated),
+            ..self
+        }
+    }
+
+    /// Same levels, left only once the reading is `hysteresis` below them
+    pub const fn with_hysteresis(self, hysteresis: f32) -> Self {
+        Self { hysteresis, ..self }
+    }
+
+    /// Level at which a reading counts as elevated
+    pub fn elevated_level(&self) -> f32 {
+        self.elevated
+            .unwrap_or(self.warning * DEFAULT_ELEVATED_FRACTION)
+    }
+
+    /// Sub-score for a reading, from 100 (fine) down to 0
+    ///
+    /// 100 up to the elevated level, then falling linearly to 75 at warning,
+    /// 50 at stressed and 25 at critical, and reaching 0 one more
+    /// stressed-to-critical span past critical. The score always lies in
+    /// the health band matching `classify`.
+    pub fn score(&self, value: f32) -> f32 {
+        let beyond = self.critical + (self.critical - self.stressed);
+        let points = [
+            (self.elevated_level(), 100.0),
+            (self.warning, HAPPY_ABOVE),
+            (self.stressed, OKAY_ABOVE),
+            (self.critical, STRESSED_ABOVE),
+            (beyond, 0.0),
+        ];
+
+        if value <= points[0].0 {
+            return 100.0;
+        }
+        for pair in points.windows(2) {
+            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+            if value < x1 {
+                return y0 + (y1 - y0) * (value - x0) / (x1 - x0);
+            }
+        }
+        0.0
+    }
+
+    /// Classify a reading; each level starts at its threshold, inclusive
+    ///
+    /// With the default CPU levels:
+    /// - Good: <56%
+    /// - Elevated: 56-70%
+    /// - Warning: 70-85%
+    /// - Stressed: 85-95%
+    /// - Critical: ≥95%
+    pub fn classify(&self, value: f32) -> MetricStatus {
+        if value >= self.critical {
+            MetricStatus::Critical
+        } else if value >= self.stressed {
+            MetricStatus::Stressed
+        } else if value >= self.warning {
+            MetricStatus::Warning
+        } else if value >= self.elevated_level() {
+            MetricStatus::Elevated
+        } else {
+            MetricStatus::Good
+        }
+    }
+}
+
+/// RAM levels used by `Pet::update`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MemoryThresholds {
+    /// RAM in use, in percent of the effective limit
+    pub percent: Thresholds,
+}
+
+impl Default for MemoryThresholds {
+    fn default() -> Self {
+        Self {
+            percent: Thresholds::new(70.0, 85.0, 95.0),
+        }
+    }
+}
+
+/// Disk levels used by `Pet::update`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DiskThresholds {
+    /// Junk as a share of total disk space, in percent
+    pub junk_percent: Thresholds,
+    /// Space used on the fullest filesystem, in percent
+    pub used_percent: Thresholds,
+}
+
+impl Default for DiskThresholds {
+    fn default() -> Self {
+        Self {
+            junk_percent: Thresholds::new(5.0, 10.0, 20.0),
+            used_percent: Thresholds::new(80.0, 90.0, 95.0),
+        }
+    }
+}
+
+/// Network levels used by `Pet::update`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkThresholds {
+    /// Busier direction of the busiest interface, in MB/s
+    pub throughput_mb_per_sec: Thresholds,
+    /// Errors plus drops per second on the worst interface
+    pub faults_per_sec: Thresholds,
+}
+
+impl Default for NetworkThresholds {
+    /// Throughput levels sit at roughly 50/75/90% of a gigabit link
+    fn default() -> Self {
+        Self {
+            throughput_mb_per_sec: Thresholds::new(60.0, 90.0, 110.0),
+            faults_per_sec: Thresholds::new(1.0, 10.0, 100.0),
+        }
+    }
+}
+
+/// CPU levels used by `Pet::update`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CpuThresholds {
+    /// Overall CPU use, in percent of the effective limit
+    pub percent: Thresholds,
+    /// 1-minute load average divided by core count; 1.0 means a full queue
+    pub load_per_core: Thresholds,
+    /// Busiest single core, in percent. The default stressed and critical
+    /// levels are above 100% and so never reached: one pegged core is worth
+    /// a warning, not an alarm
+    pub hottest_core_percent: Thresholds,
+}
+
+impl Default for CpuThresholds {
+    fn default() -> Self {
+        Self {
+            percent: Thresholds::new(70.0, 85.0, 95.0),
+            load_per_core: Thresholds::new(1.0, 1.5, 2.0),
+            hottest_core_percent: Thresholds::new(95.0, 101.0, 101.0),
+        }
+    }
+}
+
+/// Storage levels used by `Pet::update`
+///
+/// Only I/O wait is judged: raw throughput and IOPS depend too much on the
+/// hardware to have a sensible default.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IoThresholds {
+    /// Share of CPU time stalled on I/O, in percent
+    pub iowait_percent: Thresholds,
+}
+
+impl Default for IoThresholds {
+    fn default() -> Self {
+        Self {
+            iowait_percent: Thresholds::new(10.0, 25.0, 50.0),
+        }
+    }
+}
+
+/// Swap levels used by `Pet::update`
+///
+/// Swap that is merely occupied is cheap; pages moving in and out are what
+/// make a machine crawl, so both are judged.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SwapThresholds {
+    /// Share of swap in use, in percent
+    pub used_percent: Thresholds,
+    /// Pages swapped in per second
+    pub swap_in_pages_per_sec: Thresholds,
+}
+
+impl Default for SwapThresholds {
+    fn default() -> Self {
+        Self {
+            used_percent: Thresholds::new(50.0, 75.0, 90.0),
+            swap_in_pages_per_sec: Thresholds::new(100.0, 1000.0, 5000.0),
+        }
+    }
+}
+
+/// Pressure stall levels used by `Pet::update`, applied to `some avg10`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PressureThresholds {
+    pub cpu: Thresholds,
+    pub memory: Thresholds,
+    pub io: Thresholds,
+}
+
+impl Default for PressureThresholds {
+    /// CPU contention is routine on a busy desktop; memory stalls are not
+    fn default() -> Self {
+        Self {
+            cpu: Thresholds::new(25.0, 50.0, 80.0),
+            memory: Thresholds::new(5.0, 15.0, 30.0),
+            io: Thresholds::new(10.0, 25.0, 50.0),
+        }
+    }
+}
+
+/// Temperature levels used by `Pet::update`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThermalThresholds {
+    /// Degrees below a sensor's own critical limit at which it reaches each
+    /// level, so a 20 °C warning margin on a 100 °C part warns at 80 °C
+    pub below_critical_celsius: Thresholds,
+    /// Absolute levels for sensors that report no critical limit
+    pub absolute_celsius: Thresholds,
+}
+
+impl Default for ThermalThresholds {
+    fn default() -> Self {
+        Self {
+            below_critical_celsius: Thresholds::new(20.0, 10.0, 5.0),
+            absolute_celsius: Thresholds::new(80.0, 90.0, 95.0),
+        }
+    }
+}
+
+impl ThermalThresholds {
+    /// Absolute levels for one sensor
+    fn for_sensor(&self, reading: &TemperatureReading) -> Thresholds {
+        let margins = &self.below_critical_celsius;
+        match reading.critical_celsius {
+            Some(critical) => Thresholds {
+                elevated: margins.elevated.map(|m| critical - m),
+                warning: critical - margins.warning,
+                stressed: critical - margins.stressed,
+                critical: critical - margins.critical,
+                hysteresis: margins.hysteresis,
+            },
+            None => self.absolute_celsius,
+        }
+    }
+}
+
+/// Minimum time the pet stays in each state before it may leave it
+///
+/// Critical is always entered at once, whatever the current state's dwell.
+/// The defaults are zero: every sample can change the state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DwellTimes {
+    pub happy_secs: f32,
+    pub okay_secs: f32,
+    pub stressed_secs: f32,
+    pub critical_secs: f32,
+}
+
+impl DwellTimes {
+    /// Dwell time of one state
+    pub fn for_state(&self, state: PetState) -> Duration {
+        let secs = match state {
+            PetState::Happy => self.happy_secs,
+            PetState::Okay => self.okay_secs,
+            PetState::Stressed => self.stressed_secs,
+            // The metrics never lead to Sick or Dead
+            PetState::Critical | PetState::Sick | PetState::Dead => self.critical_secs,
+        };
+        Duration::try_from_secs_f32(secs.max(0.0)).unwrap_or(Duration::MAX)
+    }
+}
+
+/// Configurable thresholds for `Pet::update`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PetThresholds {
+    pub memory: MemoryThresholds,
+    pub cpu: CpuThresholds,
+    pub disk: DiskThresholds,
+    pub network: NetworkThresholds,
+    pub io: IoThresholds,
+    pub swap: SwapThresholds,
+    pub pressure: PressureThresholds,
+    pub thermal: ThermalThresholds,
+    pub dwell: DwellTimes,
+}
+
+impl PetThresholds {
+    /// Default levels plus the hysteresis and dwell times used by the app
+    ///
+    /// Tuned for a 5-second poll: a reading must clear a level by a few
+    /// points, and a calmer state must hold for half a minute or more, before
+    /// the pet changes its mind.
+    pub fn steady() -> Self {
+        let mut thresholds = Self::default();
+        thresholds.memory.percent = thresholds.memory.percent.with_hysteresis(5.0);
+        thresholds.cpu.percent = thresholds.cpu.percent.with_hysteresis(5.0);
+        thresholds.cpu.load_per_core = thresholds.cpu.load_per_core.with_hysteresis(0.1);
+        thresholds.io.iowait_percent = thresholds.io.iowait_percent.with_hysteresis(3.0);
+        thresholds.network.throughput_mb_per_sec = thresholds
+            .network
+            .throughput_mb_per_sec
+            .with_hysteresis(10.0);
+        thresholds.swap.used_percent = thresholds.swap.used_percent.with_hysteresis(5.0);
+        thresholds.thermal.below_critical_celsius = thresholds
+            .thermal
+            .below_critical_celsius
+            .with_hysteresis(3.0);
+        thresholds.thermal.absolute_celsius =
+            thresholds.thermal.absolute_celsius.with_hysteresis(3.0);
+        thresholds.dwell = DwellTimes {
+            happy_secs: 10.0,
+            okay_secs: 30.0,
+            stressed_secs: 60.0,
+            critical_secs: 60.0,
+        };
+        thresholds
+    }
+}
+
+/// Which metrics the pet takes into account
+///
+/// Disabled metrics are still collected and shown, they just never affect
+/// the pet's state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnabledMetrics {
+    pub ram: bool,
+    pub cpu: bool,
+    /// Load average and single-core saturation
+    pub load: bool,
+    pub disk_junk: bool,
+    pub disk_full: bool,
+    /// Throughput and errors/drops
+    pub network: bool,
+    pub iowait: bool,
+    /// Swap occupancy and swap-in rate
+    pub swap: bool,
+    /// Pressure stall information
+    pub pressure: bool,
+    /// Temperatures, including the too-hot flag
+    pub thermal: bool,
+}
+
+impl Default for EnabledMetrics {
+    fn default() -> Self {
+        Self {
+            ram: true,
+            cpu: true,
+            load: true,
+            disk_junk: true,
+            disk_full: true,
+            network: true,
+            iowait: true,
+            swap: true,
+            pressure: true,
+            thermal: true,
         }
     }
 }
 
+impl EnabledMetrics {
+    /// Whether the metric judged under `name` in `Pet::update` is enabled
+    fn includes(&self, name: &str) -> bool {
+        match name {
+            "ram_percent" => self.ram,
+            "cpu_percent" => self.cpu,
+            "load_per_core" | "hottest_core_percent" => self.load,
+            "disk_junk_percent" => self.disk_junk,
+            "disk_used_percent" => self.disk_full,
+            "throughput_mb_per_sec" | "faults_per_sec" => self.network,
+            "iowait_percent" => self.iowait,
+            "swap_percent" | "swap_in_pages_per_sec" => self.swap,
+            "cpu_pressure" | "memory_pressure" | "io_pressure" => self.pressure,
+            "thermal" => self.thermal,
+            _ => true,
+        }
+    }
+}
+
+/// How much each metric counts towards the health score
+///
+/// Weights are relative; a metric with weight 0 still sets the state's band
+/// when it gets bad, but no longer moves the score within it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScoreWeights {
+    pub ram: f32,
+    pub cpu: f32,
+    /// Load average and single-core saturation
+    pub load: f32,
+    pub disk_junk: f32,
+    pub disk_full: f32,
+    /// Throughput and errors/drops
+    pub network: f32,
+    pub iowait: f32,
+    /// Swap occupancy and swap-in rate
+    pub swap: f32,
+    /// Pressure stall information
+    pub pressure: f32,
+    pub thermal: f32,
+}
+
+impl Default for ScoreWeights {
+    /// RAM and CPU count double, everything else once
+    fn default() -> Self {
+        Self {
+            ram: 2.0,
+            cpu: 2.0,
+            load: 1.0,
+            disk_junk: 1.0,
+            disk_full: 1.0,
+            network: 1.0,
+            iowait: 1.0,
+            swap: 1.0,
+            pressure: 1.0,
+            thermal: 1.0,
+        }
+    }
+}
+
+impl ScoreWeights {
+    /// Weight of the metric judged under `name` in `Pet::update`
+    fn weight_of(&self, name: &str) -> f32 {
+        match name {
+            "ram_percent" => self.ram,
+            "cpu_percent" => self.cpu,
+            "load_per_core" | "hottest_core_percent" => self.load,
+            "disk_junk_percent" => self.disk_junk,
+            "disk_used_percent" => self.disk_full,
+            "throughput_mb_per_sec" | "faults_per_sec" => self.network,
+            "iowait_percent" => self.iowait,
+            "swap_percent" | "swap_in_pages_per_sec" => self.swap,
+            "cpu_pressure" | "memory_pressure" | "io_pressure" => self.pressure,
+            "thermal" => self.thermal,
+            _ => 1.0,
+        }
+    }
+}
+
+/// Running totals kept for the pet's lifetime
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PetStats {
+    /// Cleanups that succeeded, whether or not they freed anything
+    pub cleanups: u32,
+    /// Space freed by those cleanups, in MB
+    pub cleaned_mb: u64,
+    /// Daily quests completed
+    pub quests_completed: u32,
+}
+
+/// How one metric was judged in the last update
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Assessment {
+    /// Name used in transition reasons, e.g. `cpu_percent`
+    pub metric: String,
+    /// Reading that was judged; `None` when it is unknown
+    pub value: Option<f32>,
+    /// Levels bounding each band the value can fall in
+    pub thresholds: Option<Thresholds>,
+    pub status: MetricStatus,
+    /// Sub-score from 0 to 100, see `Thresholds::score`
+    pub score: Option<f32>,
+    /// Points this metric takes off the health score
+    ///
+    /// The contributions add up to 100 minus the weighted mean; the health
+    /// score can be lower still when capped by the aggregation policy.
+    pub contribution: f32,
+    /// `false` when the metric is turned off in `EnabledMetrics`
+    pub enabled: bool,
+}
+
+impl Assessment {
+    fn judged(metric: &str, value: f32, thresholds: &Thresholds) -> Self {
+        Self {
+            value: Some(value),
+            thresholds: Some(*thresholds),
+            status: thresholds.classify(value),
+            score: Some(thresholds.score(value)),
+            ..Self::unknown(metric)
+        }
+    }
+
+    fn unknown(metric: &str) -> Self {
+        Self {
+            metric: metric.to_string(),
+            value: None,
+            thresholds: None,
+            status: MetricStatus::Good,
+            score: None,
+            contribution: 0.0,
+            enabled: true,
+        }
+    }
+}
+
+/// A change of pet state and what caused it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateTransition {
+    pub from: PetState,
+    pub to: PetState,
+    /// Metrics outside their good range when the change happened,
+    /// e.g. `cpu_percent warning, ram_percent critical`
+    pub reason: String,
+}
+
 /// Pet that reflects system health
 pub struct Pet {
     state: PetState,
+    /// When `state` was entered; `None` until the first update
+    entered_at: Option<Instant>,
+    last_transition: Option<StateTransition>,
+    /// Level each metric was judged at last time, for hysteresis
+    levels: HashMap<String, MetricStatus>,
+    /// `None` while no temperature sensor can be read
+    too_hot: Option<bool>,
+    /// 0-100 health score from the last update
+    health_score: f32,
+    /// Breakdown of the last update, one entry per metric
+    assessments: Vec<Assessment>,
+    needs: NeedsTracker,
+    life: LifeTracker,
+    stats: PetStats,
+    achievements: AchievementTracker,
+    progress: ProgressTracker,
+    quests: QuestTracker,
+    illness: IllnessTracker,
+    thresholds: PetThresholds,
+    enabled: EnabledMetrics,
+    weights: ScoreWeights,
 }
 
 impl Pet {
     /// Create a new pet with default Happy state
     pub fn new() -> Self {
+        Self::with_thresholds(PetThresholds::default())
+    }
+
+    /// Create a new pet that judges load, network, storage, swap, pressure
+    /// and temperature by custom thresholds
+    pub fn with_thresholds(thresholds: PetThresholds) -> Self {
         Self {
             state: PetState::Happy,
+            entered_at: None,
+            last_transition: None,
+            levels: HashMap::new(),
+            too_hot: None,
+            health_score: 100.0,
+            assessments: Vec::new(),
+            needs: NeedsTracker::default(),
+            life: LifeTracker::default(),
+            stats: PetStats::default(),
+            achievements: AchievementTracker::default(),
+            progress: ProgressTracker::default(),
+            quests: QuestTracker::default(),
+            illness: IllnessTracker::default(),
+            thresholds,
+            enabled: EnabledMetrics::default(),
+            weights: ScoreWeights::default(),
         }
     }
 
+    /// Replace the thresholds, keeping the current state
+    pub fn set_thresholds(&mut self, thresholds: PetThresholds) {
+        self.thresholds = thresholds;
+    }
+
+    /// Choose which metrics affect the pet from the next update on
+    pub fn set_enabled_metrics(&mut self, enabled: EnabledMetrics) {
+        self.enabled = enabled;
+    }
+
+    /// Restore needs, e.g. from a save file
+    pub fn set_needs(&mut self, needs: Needs) {
+        self.needs.set_needs(needs);
+    }
+
+    /// Restore the pet's life so far, e.g. from a save file
+    pub fn set_life(&mut self, life: Life) {
+        self.life.set_life(life);
+    }
+
+    /// Restore lifetime totals, e.g. from a save file
+    pub fn set_stats(&mut self, stats: PetStats) {
+        self.stats = stats;
+    }
+
+    /// Restore earned achievements, e.g. from a save file
+    pub fn set_achievements(&mut self, achievements: Achievements) {
+        self.achievements.set_achievements(achievements);
+    }
+
+    /// Restore XP, level and streaks, e.g. from a save file
+    pub fn set_progress(&mut self, progress: Progress) {
+        self.progress.set_progress(progress);
+    }
+
+    /// Restore the day's quests, e.g. from a save file
+    pub fn set_quests(&mut self, quests: Quests) {
+        self.quests.set_quests(quests);
+    }
+
+    /// Restore sickness and the graveyard, e.g. from a save file
+    pub fn set_illness(&mut self, illness: Illness) {
+        self.illness.set_illness(illness);
+    }
+
+    /// Change when the pet falls sick, dies and recovers
+    pub fn set_illness_rules(&mut self, rules: IllnessRules) {
+        self.illness.set_rules(rules);
+    }
+
+    /// Pick up in `state` where a previous session left off
+    ///
+    /// The next update judges the metrics afresh; this only decides what
+    /// the pet shows until then and where dwell times count from. Sick and
+    /// Dead come back with the pet's `Illness` instead.
+    pub fn resume_state(&mut self, state: PetState) {
+        self.state = state.min(PetState::Critical);
+        self.entered_at = None;
+        self.levels.clear();
+    }
+
+    /// Simulate `away` spent with the app closed: needs decay and the pet
+    /// ages, unless it is dead
+    pub fn catch_up(&mut self, away: Duration) {
+        self.needs.catch_up(away);
+        if self.illness.illness().condition != Condition::Dead {
+            self.life.catch_up(away);
+        }
+    }
+
+    /// Change how fast needs rise and fall
+    pub fn set_need_rates(&mut self, rates: NeedRates) {
+        self.needs.set_rates(rates);
+    }
+
+    /// Feed the pet after a cleanup freed `deleted_mb`
+    ///
+    /// Any cleanup also cures a sick pet, or brings a new one after a death.
+    pub fn feed(&mut self, deleted_mb: u64) {
+        let shown = self.get_state();
+        if let Some(change) = self.illness.treat() {
+            self.condition_changed(shown, change);
+        }
+        self.needs.feed(deleted_mb);
+        self.stats.cleanups += 1;
+        self.stats.cleaned_mb += deleted_mb;
+    }
+
+    /// Bury a pet that died, replace it when a new one hatches, and record
+    /// the change as a transition from `shown`
+    fn condition_changed(&mut self, shown: PetState, change: ConditionChange) {
+        match (change.from, change.to) {
+            (_, Condition::Dead) => {
+                let level = self.progress.progress().level;
+                self.illness.bury(self.life.life(), level);
+            }
+            (Condition::Dead, _) => {
+                self.needs.set_needs(Needs::default());
+                self.life = LifeTracker::default();
+                self.progress = ProgressTracker::default();
+            }
+            _ => {}
+        }
+        self.last_transition = Some(StateTransition {
+            from: shown,
+            to: self.get_state(),
+            reason: change.reason.to_string(),
+        });
+    }
+
+    /// Reweigh the health score from the next update on
+    pub fn set_score_weights(&mut self, weights: ScoreWeights) {
+        self.weights = weights;
+    }
+
     /// Update pet state based on current metrics
     /// Returns the new state
     ///
     /// Updated thresholds per Domain Expert recommendations:
-    /// - RAM: 70/85/95% (was 60/80/90%)
-    /// - CPU: 70/85/95% (was 60/80/90%)
+    /// - RAM: 70/85/95% (was 60/80/90%) per `MemoryThresholds`
+    /// - CPU: 70/85/95% (was 60/80/90%), load and single-core saturation per `CpuThresholds`
     /// - Disk: 5/10/20% of total disk (percentage-based instead of absolute MB)
+    /// - Disk fullness: 80/90/95% used on the fullest filesystem
+    /// - Network: throughput and errors/drops per `NetworkThresholds`
+    /// - Storage: I/O wait per `IoThresholds`
+    /// - Swap: occupancy and swap-in rate per `SwapThresholds`
+    /// - Pressure stalls: `some avg10` per `PressureThresholds`, where available
+    /// - Temperature: the hottest sensor relative to its limit per `ThermalThresholds`
+    ///
+    /// Each metric is classified Good, Elevated, Warning, Stressed or
+    /// Critical, and `PetState::from_statuses` decides the worst state they
+    /// allow. The health score is the weighted mean of each metric's
+    /// `Thresholds::score`, capped at the top of that state's band, and the
+    /// state follows the score's band. Weights therefore move the score
+    /// within a band, while the aggregation policy picks the band.
+    ///
+    /// Each level is left only past its hysteresis, and the state changes
+    /// only as `DwellTimes` allows. Needs advance on the same clock and
+    /// affect `mood`. Time spent Happy or Okay and how quickly Critical is
+    /// left let the pet grow through its `LifeStage`s and earns XP. Daily
+    /// quests are tracked against the same sample, and any achievements
+    /// earned are unlocked.
+    ///
+    /// Staying Critical or leaving a need neglected too long makes the pet
+    /// Sick, and staying sick kills it, per `IllnessRules`; both show in
+    /// the returned state. A dead pet is buried in the graveyard and neither
+    /// grows nor earns XP until a new one hatches.
     pub fn update(&mut self, metrics: &Metrics) -> PetState {
-        // Updated RAM thresholds: 70/85/95%
-        let ram_status = Self::classify_metric(metrics.ram_percent, 70.0, 85.0, 95.0);
+        self.update_at(metrics, Instant::now())
+    }
 
-        // Updated CPU thresholds: 70/85/95% (with temporal smoothing already applied in monitor)
-        let cpu_status = Self::classify_metric(metrics.cpu_percent, 70.0, 85.0, 95.0);
+    /// Update pet state as if the metrics were sampled at `now`
+    pub fn update_at(&mut self, metrics: &Metrics, now: Instant) -> PetState {
+        let shown = self.get_state();
+        self.needs.update_at(metrics, now);
+
+        let previous = std::mem::take(&mut self.levels);
+        let last_level = |name: &str| previous.get(name).copied().unwrap_or(MetricStatus::Good);
+        let judge = |name: &str, value: f32, thresholds: &Thresholds| Assessment {
+            status: Self::classify_with_hysteresis(value, thresholds, last_level(name)),
+            ..Assessment::judged(name, value, thresholds)
+        };
+
+        // Updated RAM and CPU thresholds: 70/85/95% (CPU already smoothed in monitor)
+        let ram_status = judge(
+            "ram_percent",
+            metrics.ram_percent,
+            &self.thresholds.memory.percent,
+        );
+        let cpu = &self.thresholds.cpu;
+        let cpu_status = judge("cpu_percent", metrics.cpu_percent, &cpu.percent);
+
+        // A long run queue or a pegged core hides behind a modest average
+        let load_status = metrics
+            .load_per_core
+            .map_or(Assessment::unknown("load_per_core"), |load| {
+                judge("load_per_core", load.one, &cpu.load_per_core)
+            });
+        let hot_core_status = if metrics.single_thread_saturated {
+            judge(
+                "hottest_core_percent",
+                metrics.hottest_core_percent,
+                &cpu.hottest_core_percent,
+            )
+        } else {
+            Assessment::unknown("hottest_core_percent")
+        };
 
         // Updated disk thresholds: percentage-based (5/10/20% of total disk)
-        let disk_status = Self::classify_disk_percent(metrics.disk_junk_percent);
+        let disk = &self.thresholds.disk;
+        let disk_status = judge(
+            "disk_junk_percent",
+            metrics.disk_junk_percent,
+            &disk.junk_percent,
+        );
+
+        // A nearly full filesystem is a problem even without much junk
+        let fullest_disk = metrics
+            .disks
+            .iter()
+            .map(|d| d.used_percent)
+            .fold(0.0, f32::max);
+        let disk_full_status = judge("disk_used_percent", fullest_disk, &disk.used_percent);
+
+        // A saturated uplink or a NIC racking up errors, judged on the worst interface
+        let busiest_link = metrics
+            .networks
+            .iter()
+            .map(|n| n.peak_mb_per_sec())
+            .fold(0.0, f32::max);
+        let network = &self.thresholds.network;
+        let throughput_status = judge(
+            "throughput_mb_per_sec",
+            busiest_link,
+            &network.throughput_mb_per_sec,
+        );
+        let faults = metrics
+            .networks
+            .iter()
+            .map(|n| n.faults_per_sec)
+            .fold(0.0, f32::max);
+        let faults_status = judge("faults_per_sec", faults, &network.faults_per_sec);
+
+        // A box grinding on storage spends its idle time waiting for I/O
+        let iowait_status = judge(
+            "iowait_percent",
+            metrics.iowait_percent,
+            &self.thresholds.io.iowait_percent,
+        );
+
+        let swap = &self.thresholds.swap;
+        let swap_used_status = judge("swap_percent", metrics.swap_percent, &swap.used_percent);
+        let swap_in_status = judge(
+            "swap_in_pages_per_sec",
+            metrics.swap_in_pages_per_sec,
+            &swap.swap_in_pages_per_sec,
+        );
+
+        // Missing PSI (older kernels, other platforms) counts as no pressure
+        let pressure = &self.thresholds.pressure;
+        let stall = |name, p: Option<Pressure>, thresholds: &Thresholds| match p {
+            Some(p) => judge(name, p.some_avg10, thresholds),
+            None => Assessment::unknown(name),
+        };
+        let cpu_stall_status = stall("cpu_pressure", metrics.pressure.cpu, &pressure.cpu);
+        let memory_stall_status =
+            stall("memory_pressure", metrics.pressure.memory, &pressure.memory);
+        let io_stall_status = stall("io_pressure", metrics.pressure.io, &pressure.io);
+
+        // Each sensor is judged against its own limit; no sensors means unknown
+        let thermal = &self.thresholds.thermal;
+        let thermal_status = metrics
+            .temperatures
+            .iter()
+            .map(|t| {
+                Self::classify_with_hysteresis(
+                    t.celsius,
+                    &thermal.for_sensor(t),
+                    last_level("thermal"),
+                )
+            })
+            .max()
+            .unwrap_or(MetricStatus::Good);
+        // Reported against the sensor closest to its limit
+        let thermal_status = metrics
+            .temperatures
+            .iter()
+            .map(|t| Assessment::judged("thermal", t.celsius, &thermal.for_sensor(t)))
+            .min_by(|a, b| {
+                a.score
+                    .unwrap_or(100.0)
+                    .total_cmp(&b.score.unwrap_or(100.0))
+            })
+            .map_or(Assessment::unknown("thermal"), |closest| Assessment {
+                status: thermal_status,
+                ..closest
+            });
+        self.too_hot = (self.enabled.thermal && !metrics.temperatures.is_empty()).then(|| {
+            metrics
+                .temperatures
+                .iter()
+                .any(|t| t.celsius >= thermal.for_sensor(t).stressed)
+        });
 
         // Determine overall state based on worst metric
-        let statuses = [ram_status, cpu_status, disk_status];
+        let mut assessments = [
+            ram_status,
+            cpu_status,
+            load_status,
+            hot_core_status,
+            disk_status,
+            disk_full_status,
+            throughput_status,
+            faults_status,
+            iowait_status,
+            swap_used_status,
+            swap_in_status,
+            cpu_stall_status,
+            memory_stall_status,
+            io_stall_status,
+            thermal_status,
+        ];
 
-        // If any metric is critical, pet is critical
-        if statuses.iter().any(|&s| s == MetricStatus::Critical) {
-            self.state = PetState::Critical;
+        for assessment in &mut assessments {
+            if !self.enabled.includes(&assessment.metric) {
+                assessment.enabled = false;
+                assessment.status = MetricStatus::Good;
+                assessment.score = None;
+            }
         }
-        // If 2+ metrics are warning, or 1 warning + 1 critical, pet is stressed
-        else if statuses.iter().filter(|&&s| s != MetricStatus::Good).count() >= 2 {
-            self.state = PetState::Stressed;
+        self.levels = assessments
+            .iter()
+            .map(|a| (a.metric.clone(), a.status))
+            .collect();
+        let worst_allowed = PetState::from_statuses(&assessments.each_ref().map(|a| a.status));
+
+        // Each known metric takes its weighted share of its own shortfall off 100
+        let total_weight: f32 = assessments
+            .iter()
+            .filter(|a| a.score.is_some())
+            .map(|a| self.weights.weight_of(&a.metric))
+            .sum();
+        for assessment in &mut assessments {
+            if let Some(score) = assessment.score.filter(|_| total_weight > 0.0) {
+                assessment.contribution =
+                    self.weights.weight_of(&assessment.metric) * (100.0 - score) / total_weight;
+            }
         }
-        // If one metric is warning, pet is okay
-        else if statuses.iter().any(|&s| s == MetricStatus::Warning) {
-            self.state = PetState::Okay;
+        let mean = 100.0 - assessments.iter().map(|a| a.contribution).sum::<f32>();
+        self.health_score = mean.min(worst_allowed.max_score());
+        let target = PetState::from_score(self.health_score);
+        // Elevated metrics only explain the state when they alone set it
+        let worrying = if target == PetState::Okay
+            && assessments.iter().all(|a| a.status < MetricStatus::Warning)
+        {
+            MetricStatus::Elevated
+        } else {
+            MetricStatus::Warning
+        };
+        let reasons: Vec<String> = assessments
+            .iter()
+            .filter(|a| a.status >= worrying)
+            .map(|a| format!("{} {}", a.metric, a.status.label()))
+            .collect();
+        self.assessments = assessments.into();
+
+        // Stay put until the current state has been held long enough,
+        // unless things have become critical
+        let dwelt = self.entered_at.is_none_or(|entered| {
+            now.saturating_duration_since(entered) >= self.thresholds.dwell.for_state(self.state)
+        });
+        if target != self.state && (dwelt || target == PetState::Critical) {
+            self.last_transition = Some(StateTransition {
+                from: self.state,
+                to: target,
+                reason: if reasons.is_empty() {
+                    "all metrics good".to_string()
+                } else {
+                    reasons.join(", ")
+                },
+            });
+            self.state = target;
+            self.entered_at = Some(now);
         }
-        // All metrics good
-        else {
-            self.state = PetState::Happy;
+        self.entered_at.get_or_insert(now);
+
+        if let Some(change) = self.illness.update_at(self.state, self.needs.needs(), now) {
+            self.condition_changed(shown, change);
+        }
+        // A dead pet neither grows nor earns XP
+        let alive = self.illness.illness().condition != Condition::Dead;
+        if alive {
+            self.life.update_at(self.state, now);
+        }
+        for quest in self.quests.update_at(metrics, now) {
+            self.stats.quests_completed += 1;
+            self.progress.award(quest.reward_xp);
+        }
+        self.achievements
+            .update_at(self.state, self.stats, self.life.life(), now);
+        if alive {
+            self.progress.update_at(self.state, self.stats, now);
         }
 
-        self.state
+        self.get_state()
     }
 
-    /// Get current pet state
+    /// Get current pet state: Sick or Dead while the pet is, otherwise the
+    /// system's state
     pub fn get_state(&self) -> PetState {
+        self.illness.illness().shown_state(self.state)
+    }
+
+    /// The state the metrics alone put the pet in, ignoring illness
+    pub fn system_state(&self) -> PetState {
         self.state
     }
 
+    /// Health from 0 (critical) to 100 (perfect) as of the last update
+    ///
+    /// Unlike `get_state` this is not held back by dwell times, so it can
+    /// briefly sit in a different band than the state.
+    pub fn health_score(&self) -> f32 {
+        self.health_score
+    }
+
+    /// Hunger, energy and hygiene as of the last update
+    pub fn needs(&self) -> Needs {
+        self.needs.needs()
+    }
+
+    /// How the pet feels overall: the worse of its state and what its
+    /// needs alone would make it
+    pub fn mood(&self) -> PetState {
+        self.get_state().max(self.needs.needs().state())
+    }
+
+    /// Whether the pet is sick or dead, and every pet that died before it
+    pub fn illness(&self) -> &Illness {
+        self.illness.illness()
+    }
+
+    /// Lifetime totals such as cleanups run
+    pub fn stats(&self) -> PetStats {
+        self.stats
+    }
+
+    /// Stage, age, healthy uptime, care and evolution history
+    pub fn life(&self) -> &Life {
+        self.life.life()
+    }
+
+    pub fn stage(&self) -> LifeStage {
+        self.life.life().stage
+    }
+
+    /// The most recent evolution, if the pet has hatched at all
+    pub fn last_evolution(&self) -> Option<&Evolution> {
+        self.life.life().history.last()
+    }
+
+    /// XP, level and streaks
+    pub fn progress(&self) -> &Progress {
+        self.progress.progress()
+    }
+
+    /// Today's quests and how far along they are
+    pub fn quests(&self) -> &Quests {
+        self.quests.quests()
+    }
+
+    /// Whether the last update issued quests or moved any of them
+    pub fn quests_changed(&self) -> bool {
+        self.quests.changed()
+    }
+
+    /// Earned achievements and the progress towards the rest
+    pub fn achievements(&self) -> &Achievements {
+        self.achievements.achievements()
+    }
+
+    /// Every achievement with its unlock time and progress
+    pub fn list_achievements(&self) -> Vec<Achievement> {
+        self.achievements().list(self.stats, self.life())
+    }
+
+    /// Achievements unlocked since the last call, each returned once
+    pub fn new_achievements(&mut self) -> Vec<Achievement> {
+        let recent = self.achievements.take_recent();
+        self.list_achievements()
+            .into_iter()
+            .filter(|a| recent.contains(&a.id))
+            .collect()
+    }
+
+    /// How each metric was judged in the last update, to explain the state
+    pub fn assessments(&self) -> &[Assessment] {
+        &self.assessments
+    }
+
+    /// The most recent state change, if the pet has changed state at all
+    pub fn last_transition(&self) -> Option<&StateTransition> {
+        self.last_transition.as_ref()
+    }
+
     /// Get a description of the pet's current state
     pub fn get_icon_description(&self) -> &str {
-        self.state.description()
+        let state = self.get_state();
+        if state < PetState::Sick && self.is_too_hot() == Some(true) {
+            return "Pet is too hot - hardware is close to its thermal limit!";
+        }
+        state.description()
     }
 
     /// Get emoji for current state
     pub fn get_emoji(&self) -> &str {
-        self.state.emoji()
+        let state = self.get_state();
+        if state < PetState::Sick && self.is_too_hot() == Some(true) {
+            return "🥵";
+        }
+        state.emoji()
     }
 
-    /// Classify a metric based on thresholds
-    /// Updated thresholds:
-    /// - Good: <70%
-    /// - Warning: 70-85%
-    /// - Stressed: 85-95%
-    /// - Critical: ≥95%
-    fn classify_metric(value: f32, warning: f32, stressed: f32, critical: f32) -> MetricStatus {
-        if value >= critical {
-            MetricStatus::Critical
-        } else if value >= stressed {
-            MetricStatus::Warning
-        } else if value >= warning {
-            MetricStatus::Warning
-        } else {
-            MetricStatus::Good
-        }
+    /// Whether any sensor is within the stressed margin of its limit
+    /// Returns `None` when no temperature sensor could be read
+    pub fn is_too_hot(&self) -> Option<bool> {
+        self.too_hot
     }
 
-    /// Classify disk junk based on percentage thresholds
-    /// Updated per Domain Expert:
-    /// - Good: <5% of total disk
-    /// - Warning: 5-10% of total disk
-    /// - Stressed: 10-20% of total disk
-    /// - Critical: ≥20% of total disk
-    fn classify_disk_percent(percent: f32) -> MetricStatus {
-        if percent >= 20.0 {
-            MetricStatus::Critical
-        } else if percent >= 10.0 {
-            MetricStatus::Warning
-        } else if percent >= 5.0 {
-            MetricStatus::Warning
-        } else {
-            MetricStatus::Good
+    /// Classify a metric, holding on to its `previous` level until the
+    /// reading has dropped the hysteresis margin below it
+    fn classify_with_hysteresis(
+        value: f32,
+        thresholds: &Thresholds,
+        previous: MetricStatus,
+    ) -> MetricStatus {
+        let entered = thresholds.classify(value);
+        if entered >= previous {
+            return entered;
         }
+        thresholds
+            .classify(value + thresholds.hysteresis.max(0.0))
+            .min(previous)
     }
 }
 
@@ -146,11 +1273,3 @@ impl Default for Pet {
         Self::new()
     }
 }
-
-/// Internal metric classification
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum MetricStatus {
-    Good,
-    Warning,
-    Critical,
-}
@@ -1,41 +1,148 @@
 use crate::commands::AppState;
-use std::sync::Arc;
-use std::time::Duration;
-use tauri::{AppHandle, Emitter};
+use crate::config::{self, Config, ConfigWatcher};
+use crate::pet::PetState;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
 use tokio::time;
 
+/// How often the pet is saved while nothing else prompts it; needs drift
+/// continuously
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
 /// Start background polling of system metrics
-/// Polls every 5 seconds and emits events to frontend
-pub fn start_polling(app_handle: AppHandle, state: Arc<AppState>) {
-    tokio::spawn(async move {
-        let mut interval = time::interval(Duration::from_secs(5));
+/// Polls every 5 seconds (or as configured) and emits events to frontend
+///
+/// The config file is checked before every sample, so edits take effect on
+/// the next tick without a restart.
+pub fn start_polling(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut poll_interval = Config::default().poll_interval();
+        let mut interval = time::interval(poll_interval);
+        let mut watcher = config::config_path().map(ConfigWatcher::new);
+        let mut last_save = Instant::now();
 
         loop {
             interval.tick().await;
 
-            // Refresh metrics
-            if let Ok(mut monitor) = state.monitor.lock() {
-                monitor.refresh();
-                let metrics = monitor.get_metrics();
-
-                // Update pet state
-                if let Ok(mut pet) = state.pet.lock() {
-                    let pet_state = pet.update(&metrics);
-
-                    // Emit event to frontend with updated metrics
-                    let event_data = serde_json::json!({
-                        "metrics": metrics,
-                        "pet_state": pet_state,
-                        "pet_emoji": pet.get_emoji(),
-                        "pet_description": pet.get_icon_description(),
-                    });
-
-                    // Emit to all windows
-                    if let Err(e) = app_handle.emit("metrics-update", event_data) {
-                        eprintln!("Failed to emit metrics-update event: {}", e);
+            let state = app_handle.state::<AppState>();
+
+            if let Some(reloaded) = watcher.as_mut().and_then(ConfigWatcher::poll) {
+                match reloaded {
+                    Ok(config) => {
+                        if let (Ok(mut monitor), Ok(mut pet)) =
+                            (state.monitor.lock(), state.pet.lock())
+                        {
+                            config.apply(&mut monitor, &mut pet);
+                        }
+                        if config.poll_interval() != poll_interval {
+                            poll_interval = config.poll_interval();
+                            interval = time::interval_at(
+                                time::Instant::now() + poll_interval,
+                                poll_interval,
+                            );
+                        }
+                        if let Err(e) = app_handle.emit("config-reloaded", &config) {
+                            eprintln!("Failed to emit config-reloaded event: {}", e);
+                        }
                     }
+                    Err(e) => {
+                        // Keep running on the last good config
+                        let path = watcher.as_ref().map(|w| w.path().display().to_string());
+                        eprintln!("Ignoring config {}: {}", path.unwrap_or_default(), e);
+                        if let Err(e) = app_handle.emit("config-error", e.to_string()) {
+                            eprintln!("Failed to emit config-error event: {}", e);
+                        }
+                    }
+                }
+            }
+
+            // Refresh metrics; directory scans run on their own worker, so the
+            // monitor lock is only held for the quick sysinfo/proc reads
+            let metrics = match state.monitor.lock() {
+                Ok(mut monitor) => {
+                    monitor.refresh();
+                    monitor.get_metrics()
+                }
+                Err(e) => {
+                    eprintln!("Monitor lock poisoned: {}", e);
+                    continue;
+                }
+            };
+
+            if let Ok(mut last) = state.metrics.lock() {
+                *last = Some(metrics.clone());
+            }
+
+            // Update pet state; nothing else does, so every change below is
+            // announced and saved
+            let Ok(mut pet) = state.pet.lock() else {
+                continue;
+            };
+            let previous_state = pet.get_state();
+            let previous_stage = pet.stage();
+            let pet_state = pet.update(&metrics);
+
+            // Emit event to frontend with updated metrics
+            let event_data = serde_json::json!({
+                "metrics": metrics,
+                "pet_state": pet_state,
+                "health_score": pet.health_score(),
+                "pet_emoji": pet.get_emoji(),
+                "pet_description": pet.get_icon_description(),
+                "last_transition": pet.last_transition(),
+                "assessments": pet.assessments(),
+                "needs": pet.needs(),
+                "mood": pet.mood(),
+                "stage": pet.stage(),
+                "life": pet.life(),
+                "illness": pet.illness(),
+            });
+
+            // Emit to all windows
+            if let Err(e) = app_handle.emit("metrics-update", event_data) {
+                eprintln!("Failed to emit metrics-update event: {}", e);
+            }
+
+            // Hysteresis and dwell times keep this rare enough to alert on
+            if pet_state != previous_state {
+                if let Err(e) = app_handle.emit("pet-state-changed", pet.last_transition()) {
+                    eprintln!("Failed to emit pet-state-changed event: {}", e);
+                }
+            }
+
+            if pet_state == PetState::Dead && previous_state != PetState::Dead {
+                if let Err(e) = app_handle.emit("pet-died", pet.illness().graveyard.last()) {
+                    eprintln!("Failed to emit pet-died event: {}", e);
+                }
+            }
+
+            if pet.stage() != previous_stage {
+                if let Err(e) = app_handle.emit("pet-evolved", pet.last_evolution()) {
+                    eprintln!("Failed to emit pet-evolved event: {}", e);
                 }
             }
+            if pet.quests_changed() {
+                if let Err(e) = app_handle.emit("quest-progress", pet.quests()) {
+                    eprintln!("Failed to emit quest-progress event: {}", e);
+                }
+            }
+
+            let unlocked = pet.new_achievements();
+            for achievement in &unlocked {
+                if let Err(e) = app_handle.emit("achievement-unlocked", achievement) {
+                    eprintln!("Failed to emit achievement-unlocked event: {}", e);
+                }
+            }
+            let changed = pet_state != previous_state
+                || pet.stage() != previous_stage
+                || !unlocked.is_empty()
+                || pet.quests_changed();
+            drop(pet);
+
+            if changed || last_save.elapsed() >= SAVE_INTERVAL {
+                state.save();
+                last_save = Instant::now();
+            }
         }
     });
 }
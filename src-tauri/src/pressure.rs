@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+/// Pressure stall averages for one resource, in percent of wall time
+///
+/// `some` is the share of time at least one task was stalled on the
+/// resource, `full` the share of time all non-idle tasks were stalled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pressure {
+    pub some_avg10: f32,
+    pub some_avg60: f32,
+    pub full_avg10: f32,
+    pub full_avg60: f32,
+}
+
+/// Linux pressure stall information (PSI) from `/proc/pressure`
+///
+/// Each resource is `None` when the kernel does not expose it (PSI disabled,
+/// kernels before 4.20, or non-Linux platforms).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PressureStall {
+    pub cpu: Option<Pressure>,
+    pub memory: Option<Pressure>,
+    pub io: Option<Pressure>,
+}
+
+/// Parse one `/proc/pressure/<resource>` file
+///
+/// Older kernels omit the `full` line for CPU; it then reads as zero.
+pub fn parse_pressure(contents: &str) -> Option<Pressure> {
+    let mut pressure = Pressure::default();
+    let mut seen_some = false;
+
+    for line in contents.lines() {
+        let mut parts = line.split_whitespace();
+        let kind = parts.next();
+
+        let mut avg10 = None;
+        let mut avg60 = None;
+        for part in parts {
+            match part.split_once('=') {
+                Some(("avg10", v)) => avg10 = v.parse::<f32>().ok(),
+                Some(("avg60", v)) => avg60 = v.parse::<f32>().ok(),
+                _ => {}
+            }
+        }
+
+        match kind {
+            Some("some") => {
+                pressure.some_avg10 = avg10?;
+                pressure.some_avg60 = avg60?;
+                seen_some = true;
+            }
+            Some("full") => {
+                pressure.full_avg10 = avg10?;
+                pressure.full_avg60 = avg60?;
+            }
+            _ => {}
+        }
+    }
+
+    seen_some.then_some(pressure)
+}
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// A single process ranked by one resource, reported in `Metrics`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessUsage {
+    pub pid: u32,
+    pub name: String,
+    /// Owning user name, if it could be resolved
+    pub user: Option<String>,
+    /// CPU percent of the whole machine for `top_cpu`,
+    /// resident memory in MB for `top_memory`
+    pub value: f32,
+}
+
+/// Keep the `n` entries with the highest value, largest first
+///
+/// Entries with a zero value are dropped, so an idle machine reports an empty
+/// list rather than arbitrary processes. Ties are broken by pid to keep the
+/// order stable between polls.
+pub fn top_n(mut processes: Vec<ProcessUsage>, n: usize) -> Vec<ProcessUsage> {
+    processes.retain(|p| p.value > 0.0);
+    processes.sort_by(|a, b| {
+        b.value
+            .partial_cmp(&a.value)
+            .unwrap_or(Ordering::Equal)
+            .then(a.pid.cmp(&b.pid))
+    });
+    processes.truncate(n);
+    processes
+}
@@ -0,0 +1,244 @@
+use crate::pet::{PetState, PetStats};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// XP earned per minute spent Happy or Okay
+const XP_PER_HEALTHY_MINUTE: f64 = 1.0;
+/// XP lost per minute spent Critical
+const XP_LOST_PER_CRITICAL_MINUTE: f64 = 5.0;
+/// XP between level 1 and 2; each level after needs this much more than the
+/// one before
+const XP_LEVEL_STEP: f64 = 100.0;
+/// Finished days kept in `Progress::days`
+const HISTORY_DAYS: usize = 30;
+const SECS_PER_DAY: u64 = 24 * 3600;
+
+/// Maps instants to wall-clock time, to tell calendar days apart
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct WallClock {
+    anchor: Option<(Instant, SystemTime)>,
+}
+
+impl WallClock {
+    /// Take `now` to be wall-clock time `wall`
+    pub(crate) fn set(&mut self, now: Instant, wall: SystemTime) {
+        self.anchor = Some((now, wall));
+    }
+
+    /// Seconds since the Unix epoch at `now`, anchored to the system clock
+    /// the first time it is asked
+    pub(crate) fn unix_secs(&mut self, now: Instant) -> u64 {
+        let (anchor, wall) = *self.anchor.get_or_insert((now, SystemTime::now()));
+        let wall_now = match now.checked_duration_since(anchor) {
+            Some(ahead) => wall + ahead,
+            None => wall - anchor.duration_since(now),
+        };
+        wall_now
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO)
+            .as_secs()
+    }
+
+    /// The UTC day `unix_secs` falls on, counted from the Unix epoch
+    pub(crate) fn day(unix_secs: u64) -> u64 {
+        unix_secs / SECS_PER_DAY
+    }
+}
+
+/// One calendar day (UTC) of the pet's life
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DayRecord {
+    /// Days since the Unix epoch
+    pub day: u64,
+    /// False once the pet has been Critical that day
+    pub healthy: bool,
+    /// Cleanups run that day
+    pub cleanups: u32,
+    /// Net XP earned that day
+    pub xp: f64,
+}
+
+impl DayRecord {
+    fn new(day: u64) -> Self {
+        Self {
+            day,
+            healthy: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// The first time the pet reached a level
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LevelUp {
+    pub level: u32,
+    /// Seconds since the Unix epoch
+    pub at_unix_secs: u64,
+}
+
+/// Experience, level and streaks, kept between sessions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Progress {
+    pub xp: f64,
+    /// Follows `xp`, so it drops again if Critical time costs enough XP
+    pub level: u32,
+    /// Consecutive finished days without a Critical state
+    pub healthy_streak: u32,
+    pub best_healthy_streak: u32,
+    /// Consecutive finished days with at least one cleanup
+    pub cleanup_streak: u32,
+    pub best_cleanup_streak: u32,
+    /// The day in progress
+    pub today: Option<DayRecord>,
+    /// The last 30 finished days the app saw, oldest first
+    pub days: Vec<DayRecord>,
+    /// Each level the first time it was reached, lowest first
+    pub level_ups: Vec<LevelUp>,
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Self {
+            xp: 0.0,
+            level: 1,
+            healthy_streak: 0,
+            best_healthy_streak: 0,
+            cleanup_streak: 0,
+            best_cleanup_streak: 0,
+            today: None,
+            days: Vec::new(),
+            level_ups: Vec::new(),
+        }
+    }
+}
+
+impl Progress {
+    /// Total XP needed to reach `level`: 0, 100, 300, 600, ...
+    pub fn xp_for_level(level: u32) -> f64 {
+        let n = level.saturating_sub(1) as f64;
+        XP_LEVEL_STEP * n * (n + 1.0) / 2.0
+    }
+
+    /// The level `xp` is worth
+    pub fn level_for(xp: f64) -> u32 {
+        let mut level = 1;
+        while xp >= Self::xp_for_level(level + 1) {
+            level += 1;
+        }
+        level
+    }
+
+    /// Close `today` and count it towards the streaks
+    ///
+    /// A day the app never saw breaks both streaks.
+    fn finish_day(&mut self, day: DayRecord) {
+        let follows = self.days.last().is_some_and(|last| last.day + 1 == day.day);
+        let extend = |streak: u32, kept: bool| match (kept, follows) {
+            (false, _) => 0,
+            (true, true) => streak + 1,
+            (true, false) => 1,
+        };
+        self.healthy_streak = extend(self.healthy_streak, day.healthy);
+        self.cleanup_streak = extend(self.cleanup_streak, day.cleanups > 0);
+        self.best_healthy_streak = self.best_healthy_streak.max(self.healthy_streak);
+        self.best_cleanup_streak = self.best_cleanup_streak.max(self.cleanup_streak);
+
+        self.days.push(day);
+        let excess = self.days.len().saturating_sub(HISTORY_DAYS);
+        self.days.drain(..excess);
+    }
+}
+
+/// Progress plus the clocks it advances on
+#[derive(Debug, Clone, Default)]
+pub struct ProgressTracker {
+    progress: Progress,
+    /// When the last update happened and the state the pet was left in
+    last_tick: Option<(Instant, PetState)>,
+    clock: WallClock,
+    /// Cleanup count at the last update
+    last_cleanups: Option<u32>,
+}
+
+impl ProgressTracker {
+    pub fn new(progress: Progress) -> Self {
+        Self {
+            progress,
+            ..Default::default()
+        }
+    }
+
+    pub fn progress(&self) -> &Progress {
+        &self.progress
+    }
+
+    /// Replace the progress, e.g. with one restored from a save file
+    pub fn set_progress(&mut self, progress: Progress) {
+        self.progress = progress;
+        self.last_cleanups = None;
+    }
+
+    /// Take `now` to be wall-clock time `wall` when telling days apart
+    ///
+    /// The first update does this with the system clock.
+    pub fn set_clock(&mut self, now: Instant, wall: SystemTime) {
+        self.clock.set(now, wall);
+    }
+
+    /// Grant bonus XP, e.g. for a completed quest; the level follows on the
+    /// next update
+    pub fn award(&mut self, xp: f64) {
+        self.progress.xp += xp;
+        if let Some(today) = &mut self.progress.today {
+            today.xp += xp;
+        }
+    }
+
+    /// Advance to `now`, the pet being in `state` from now on
+    ///
+    /// The time since the last call earns or costs XP according to the
+    /// state the pet was in, and days roll over at midnight UTC. Cleanups
+    /// are counted from `stats`.
+    pub fn update_at(&mut self, state: PetState, stats: PetStats, now: Instant) {
+        let unix_secs = self.clock.unix_secs(now);
+        let progress = &mut self.progress;
+
+        // Time since the last update belongs to the day it started in
+        if let Some((last, last_state)) = self.last_tick {
+            let minutes = now.saturating_duration_since(last).as_secs_f64() / 60.0;
+            let earned = match last_state {
+                PetState::Happy | PetState::Okay => XP_PER_HEALTHY_MINUTE * minutes,
+                PetState::Stressed | PetState::Sick | PetState::Dead => 0.0,
+                PetState::Critical => -XP_LOST_PER_CRITICAL_MINUTE * minutes,
+            };
+            let gained = (progress.xp + earned).max(0.0) - progress.xp;
+            progress.xp += gained;
+            if let Some(today) = &mut progress.today {
+                today.xp += gained;
+            }
+        }
+        self.last_tick = Some((now, state));
+
+        let day = WallClock::day(unix_secs);
+        if let Some(finished) = progress.today.take_if(|today| today.day != day) {
+            progress.finish_day(finished);
+        }
+        let today = progress.today.get_or_insert(DayRecord::new(day));
+        if state == PetState::Critical {
+            today.healthy = false;
+        }
+        let cleanups = stats.cleanups;
+        today.cleanups += cleanups.saturating_sub(self.last_cleanups.unwrap_or(cleanups));
+        self.last_cleanups = Some(cleanups);
+
+        progress.level = Progress::level_for(progress.xp);
+        let best = progress.level_ups.last().map_or(1, |up| up.level);
+        for level in best + 1..=progress.level {
+            progress.level_ups.push(LevelUp {
+                level,
+                at_unix_secs: unix_secs,
+            });
+        }
+    }
+}
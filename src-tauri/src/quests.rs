@@ -0,0 +1,259 @@
+use crate::monitor::Metrics;
+use crate::progress::WallClock;
+use serde::{Deserialize, Serialize};
+use std::time::{Instant, SystemTime};
+
+/// Junk share of the disk the junk quest asks to get below, in percent
+const JUNK_TARGET_PERCENT: f32 = 2.0;
+/// Average CPU the calm quest asks to stay under, in percent
+const CALM_CPU_PERCENT: f32 = 50.0;
+/// How long the calm quest's average is taken over, in seconds
+const CALM_SECS: f64 = 3600.0;
+/// Smallest resident memory worth a quest to close the process, in MB
+const HOG_MIN_MB: f32 = 500.0;
+/// XP for completing any quest
+const QUEST_XP: f64 = 50.0;
+
+/// What a quest asks for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum QuestGoal {
+    /// Get junk from `from_percent` to below `below_percent` of the disk
+    TrimJunk {
+        from_percent: f32,
+        below_percent: f32,
+    },
+    /// Keep the average CPU under `below_percent` over `for_secs`
+    CalmCpu { below_percent: f32, for_secs: f64 },
+    /// Close the process using the most memory, or at least get it out of
+    /// the top consumers
+    CloseProcess {
+        pid: u32,
+        name: String,
+        memory_mb: f32,
+    },
+}
+
+impl QuestGoal {
+    pub fn description(&self) -> String {
+        match self {
+            QuestGoal::TrimJunk { below_percent, .. } => {
+                format!("Get junk below {}% of the disk", below_percent)
+            }
+            QuestGoal::CalmCpu {
+                below_percent,
+                for_secs,
+            } => format!(
+                "Keep average CPU under {}% for {} minutes",
+                below_percent,
+                for_secs / 60.0
+            ),
+            QuestGoal::CloseProcess {
+                name, memory_mb, ..
+            } => format!("Close {}, which is using {:.0} MB", name, memory_mb),
+        }
+    }
+}
+
+/// One of the day's quests
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Quest {
+    pub goal: QuestGoal,
+    pub description: String,
+    /// How close it is, from 0 to 1
+    pub progress: f32,
+    pub reward_xp: f64,
+    /// When it was completed, in seconds since the Unix epoch
+    pub completed_at_unix_secs: Option<u64>,
+}
+
+impl Quest {
+    fn new(goal: QuestGoal) -> Self {
+        Self {
+            description: goal.description(),
+            goal,
+            progress: 0.0,
+            reward_xp: QUEST_XP,
+            completed_at_unix_secs: None,
+        }
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.completed_at_unix_secs.is_some()
+    }
+}
+
+/// The day's quests, kept between sessions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Quests {
+    /// UTC day they were issued for, counted from the Unix epoch
+    pub day: Option<u64>,
+    pub quests: Vec<Quest>,
+}
+
+impl Quests {
+    /// Quests for `day` suited to the machine as `metrics` finds it
+    ///
+    /// Calming the CPU is always on offer; trimming junk only when there is
+    /// enough of it, and closing a process only when one is using a lot of
+    /// memory.
+    pub fn generate(day: u64, metrics: &Metrics) -> Self {
+        let mut quests = Vec::new();
+        if !metrics.junk_scan_incomplete && metrics.disk_junk_percent >= JUNK_TARGET_PERCENT {
+            quests.push(Quest::new(QuestGoal::TrimJunk {
+                from_percent: metrics.disk_junk_percent,
+                below_percent: JUNK_TARGET_PERCENT,
+            }));
+        }
+        quests.push(Quest::new(QuestGoal::CalmCpu {
+            below_percent: CALM_CPU_PERCENT,
+            for_secs: CALM_SECS,
+        }));
+        if let Some(hog) = metrics.top_memory.first().filter(|p| p.value >= HOG_MIN_MB) {
+            quests.push(Quest::new(QuestGoal::CloseProcess {
+                pid: hog.pid,
+                name: hog.name.clone(),
+                memory_mb: hog.value,
+            }));
+        }
+
+        Self {
+            day: Some(day),
+            quests,
+        }
+    }
+}
+
+/// Quests plus the clocks they advance on
+#[derive(Debug, Clone, Default)]
+pub struct QuestTracker {
+    quests: Quests,
+    clock: WallClock,
+    last_tick: Option<Instant>,
+    /// Start of the current CPU averaging window and the CPU-seconds used
+    /// in it so far
+    cpu_window: Option<(Instant, f64)>,
+    /// Whether the last update moved any quest
+    changed: bool,
+}
+
+impl QuestTracker {
+    pub fn new(quests: Quests) -> Self {
+        Self {
+            quests,
+            ..Default::default()
+        }
+    }
+
+    pub fn quests(&self) -> &Quests {
+        &self.quests
+    }
+
+    /// Replace the quests, e.g. with ones restored from a save file
+    pub fn set_quests(&mut self, quests: Quests) {
+        self.quests = quests;
+        self.cpu_window = None;
+    }
+
+    /// Take `now` to be wall-clock time `wall` when telling days apart
+    ///
+    /// The first update does this with the system clock.
+    pub fn set_clock(&mut self, now: Instant, wall: SystemTime) {
+        self.clock.set(now, wall);
+    }
+
+    /// Whether the last update issued quests, or moved any of them by at
+    /// least a percent
+    pub fn changed(&self) -> bool {
+        self.changed
+    }
+
+    /// Track the quests against a sample taken at `now`, issuing new ones
+    /// when a new day (UTC) starts
+    ///
+    /// Returns the quests completed by this sample.
+    pub fn update_at(&mut self, metrics: &Metrics, now: Instant) -> Vec<Quest> {
+        let unix_secs = self.clock.unix_secs(now);
+        let day = WallClock::day(unix_secs);
+        let before = Self::milestones(&self.quests);
+        if self.quests.day != Some(day) {
+            self.quests = Quests::generate(day, metrics);
+            self.cpu_window = None;
+        }
+
+        // CPU-seconds since the last sample, counted at this sample's rate
+        let secs = self.last_tick.map_or(0.0, |last| {
+            now.saturating_duration_since(last).as_secs_f64()
+        });
+        self.last_tick = Some(now);
+        let (window_start, busy) = match &mut self.cpu_window {
+            Some((start, busy)) => {
+                *busy += metrics.cpu_percent as f64 * secs;
+                (*start, *busy)
+            }
+            None => *self.cpu_window.insert((now, 0.0)),
+        };
+        let window = now.saturating_duration_since(window_start).as_secs_f64();
+        let mean_cpu = if window > 0.0 { busy / window } else { 0.0 };
+
+        let mut completed = Vec::new();
+        let mut restart_window = false;
+        for quest in self.quests.quests.iter_mut().filter(|q| !q.is_completed()) {
+            let done = match &quest.goal {
+                // Partial scans undercount junk
+                QuestGoal::TrimJunk { .. } if metrics.junk_scan_incomplete => false,
+                QuestGoal::TrimJunk {
+                    from_percent,
+                    below_percent,
+                } => {
+                    let trimmed = (from_percent - metrics.disk_junk_percent)
+                        / (from_percent - below_percent).max(f32::EPSILON);
+                    quest.progress = trimmed.clamp(0.0, 1.0);
+                    metrics.disk_junk_percent < *below_percent
+                }
+                QuestGoal::CalmCpu {
+                    below_percent,
+                    for_secs,
+                } => {
+                    let calm = mean_cpu < *below_percent as f64;
+                    quest.progress = if calm {
+                        (window / for_secs).min(1.0) as f32
+                    } else {
+                        0.0
+                    };
+                    // The average is judged once the window is full
+                    restart_window = window >= *for_secs;
+                    calm && window >= *for_secs
+                }
+                // Processes are only listed where they can be read at all
+                QuestGoal::CloseProcess { pid, .. } => {
+                    !metrics.top_memory.is_empty()
+                        && metrics.top_memory.iter().all(|p| p.pid != *pid)
+                }
+            };
+            if done {
+                quest.progress = 1.0;
+                quest.completed_at_unix_secs = Some(unix_secs);
+                completed.push(quest.clone());
+            }
+        }
+        if restart_window {
+            self.cpu_window = Some((now, 0.0));
+        }
+
+        self.changed = Self::milestones(&self.quests) != before;
+        completed
+    }
+
+    /// What `changed` compares: the day, and each quest's whole-percent
+    /// progress and completion, so CPU seconds ticking by do not count
+    fn milestones(quests: &Quests) -> (Option<u64>, Vec<(u32, bool)>) {
+        let marks = quests
+            .quests
+            .iter()
+            .map(|q| ((q.progress * 100.0) as u32, q.is_completed()))
+            .collect();
+        (quests.day, marks)
+    }
+}
@@ -0,0 +1,259 @@
+use crate::achievements::Achievements;
+use crate::illness::Illness;
+use crate::life::Life;
+use crate::needs::Needs;
+use crate::pet::{Pet, PetState, PetStats};
+use crate::progress::Progress;
+use crate::quests::Quests;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Format version written by this build
+///
+/// - 1: needs and life, without a version field
+/// - 2: adds the version, state, stats and last-seen time
+///
+/// Fields that can start from their defaults, such as achievements, XP,
+/// quests and illness, are added without a new version.
+pub const SAVE_VERSION: u32 = 2;
+
+/// What the pet keeps between sessions
+///
+/// Missing fields take their defaults, so a save that lacks some, such as
+/// one edited by hand, still loads.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaveData {
+    pub version: u32,
+    /// When the save was written, in seconds since the Unix epoch; `None`
+    /// for saves migrated from before it was recorded
+    pub last_seen_unix_secs: Option<u64>,
+    /// The state the metrics put the pet in; sickness and death are kept in
+    /// `illness`
+    pub state: PetState,
+    pub needs: Needs,
+    pub life: Life,
+    pub stats: PetStats,
+    pub achievements: Achievements,
+    pub progress: Progress,
+    pub quests: Quests,
+    pub illness: Illness,
+}
+
+impl Default for SaveData {
+    fn default() -> Self {
+        Self {
+            version: SAVE_VERSION,
+            last_seen_unix_secs: None,
+            state: PetState::Happy,
+            needs: Needs::default(),
+            life: Life::default(),
+            stats: PetStats::default(),
+            achievements: Achievements::default(),
+            progress: Progress::default(),
+            quests: Quests::default(),
+            illness: Illness::default(),
+        }
+    }
+}
+
+/// Why the save file could not be read or written
+#[derive(Debug, Error)]
+pub enum SaveError {
+    #[error("cannot access {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("unreadable save file: {0}")]
+    Format(#[from] serde_json::Error),
+    #[error("save file version {0} is newer than this app understands")]
+    TooNew(u64),
+}
+
+impl SaveError {
+    /// Whether `load_or_recover` left the unreadable save where it was, so
+    /// saving over it would destroy it
+    ///
+    /// Only a damaged save is moved aside; one from a newer build, or one
+    /// that could not be read for now, is still there to load next time.
+    pub fn leaves_save_in_place(&self) -> bool {
+        !matches!(self, SaveError::Format(_))
+    }
+}
+
+impl SaveData {
+    /// Snapshot `pet` as of `now`
+    pub fn capture(pet: &Pet, now: SystemTime) -> Self {
+        Self {
+            version: SAVE_VERSION,
+            last_seen_unix_secs: now.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs()),
+            state: pet.system_state(),
+            needs: pet.needs(),
+            life: pet.life().clone(),
+            stats: pet.stats(),
+            achievements: pet.achievements().clone(),
+            progress: pet.progress().clone(),
+            quests: pet.quests().clone(),
+            illness: pet.illness().clone(),
+        }
+    }
+
+    /// Put this save back into `pet`, simulating the time since it was
+    /// written if it is older than `now`
+    ///
+    /// Returns how long the app was closed, when known.
+    pub fn restore_into(&self, pet: &mut Pet, now: SystemTime) -> Option<Duration> {
+        pet.resume_state(self.state);
+        pet.set_needs(self.needs);
+        pet.set_life(self.life.clone());
+        pet.set_stats(self.stats);
+        pet.set_achievements(self.achievements.clone());
+        pet.set_progress(self.progress.clone());
+        pet.set_quests(self.quests.clone());
+        pet.set_illness(self.illness.clone());
+
+        let last_seen = UNIX_EPOCH + Duration::from_secs(self.last_seen_unix_secs?);
+        let away = now.duration_since(last_seen).ok()?;
+        pet.catch_up(away);
+        Some(away)
+    }
+
+    /// Parse a save file of any known version
+    pub fn parse(text: &str) -> Result<Self, SaveError> {
+        let value: Value = serde_json::from_str(text)?;
+        Ok(serde_json::from_value(migrate(value)?)?)
+    }
+
+    /// Load the save file at `path`; `None` when there is none yet
+    pub fn load(path: &Path) -> Result<Option<Self>, SaveError> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(source) => Err(SaveError::Io {
+                path: path.to_path_buf(),
+                source,
+            }),
+        }
+    }
+
+    /// Load the save file, recovering from damage where possible
+    ///
+    /// A damaged save is moved aside to `<name>.corrupt` so it is not
+    /// overwritten, and the backup left by the previous `save` is used
+    /// instead. Returns whatever could be restored along with the problem,
+    /// if any, to report; see `SaveError::leaves_save_in_place`.
+    pub fn load_or_recover(path: &Path) -> (Option<Self>, Option<SaveError>) {
+        let problem = match Self::load(path) {
+            Ok(Some(data)) => return (Some(data), None),
+            Ok(None) => None,
+            Err(SaveError::Format(e)) => {
+                match std::fs::rename(path, sibling(path, "corrupt")) {
+                    Ok(()) => Some(SaveError::Format(e)),
+                    // Still in place, so it must not be saved over either
+                    Err(source) => Some(SaveError::Io {
+                        path: path.to_path_buf(),
+                        source,
+                    }),
+                }
+            }
+            Err(e) => Some(e),
+        };
+        let backup = Self::load(&sibling(path, "bak")).ok().flatten();
+        (backup, problem)
+    }
+
+    /// Write the save file atomically, creating its directory if needed
+    ///
+    /// The new contents go to a temporary file that replaces the save in a
+    /// single rename, so a crash leaves either the old or the new save. The
+    /// old save is kept as `<name>.bak` for `load_or_recover`.
+    pub fn save(&self, path: &Path) -> Result<(), SaveError> {
+        let io = |source| SaveError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(io)?;
+        }
+
+        let text = serde_json::to_string_pretty(self)?;
+        let temp = sibling(path, "tmp");
+        let mut file = std::fs::File::create(&temp).map_err(io)?;
+        file.write_all(text.as_bytes()).map_err(io)?;
+        file.sync_all().map_err(io)?;
+        drop(file);
+
+        if path.exists() {
+            std::fs::copy(path, sibling(path, "bak")).map_err(io)?;
+        }
+        std::fs::rename(&temp, path).map_err(io)
+    }
+}
+
+/// Upgrade a save of any older version to `SAVE_VERSION`, one step at a time
+fn migrate(mut value: Value) -> Result<Value, SaveError> {
+    let Some(fields) = value.as_object_mut() else {
+        // Let deserializing report what is wrong with it
+        return Ok(value);
+    };
+    let mut version = fields.get("version").and_then(Value::as_u64).unwrap_or(1);
+    if version > SAVE_VERSION as u64 {
+        return Err(SaveError::TooNew(version));
+    }
+
+    while version < SAVE_VERSION as u64 {
+        match version {
+            1 => migrate_v1(fields),
+            _ => unreachable!("every older version has a migration"),
+        }
+        version += 1;
+        fields.insert("version".into(), version.into());
+    }
+    Ok(value)
+}
+
+/// v1 had only needs and, later, life
+fn migrate_v1(fields: &mut Map<String, Value>) {
+    fields
+        .entry("life")
+        .or_insert_with(|| serde_json::json!({}));
+    fields.insert("last_seen_unix_secs".into(), Value::Null);
+    fields.insert("state".into(), serde_json::json!(PetState::Happy));
+    fields.insert("stats".into(), serde_json::json!({}));
+}
+
+/// `path` with `suffix` added to its file name, e.g. `save.json.bak`
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// `save.json` in the per-user data directory
+///
+/// `$XDG_DATA_HOME/sysadmin-tamagotchi` (falling back to `~/.local/share`) on
+/// Unix, `%APPDATA%\sysadmin-tamagotchi` on Windows. `None` when none of those
+/// variables is set.
+pub fn save_path() -> Option<PathBuf> {
+    save_path_with(|key| std::env::var(key).ok())
+}
+
+/// `save_path` with an injectable environment lookup
+pub fn save_path_with(env: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    let non_empty = |key: &str| env(key).filter(|v| !v.is_empty()).map(PathBuf::from);
+
+    let dir = if cfg!(windows) {
+        non_empty("APPDATA")
+    } else {
+        non_empty("XDG_DATA_HOME")
+            .or_else(|| non_empty("HOME").map(|h| h.join(".local").join("share")))
+    }?;
+    Some(dir.join("sysadmin-tamagotchi").join("save.json"))
+}
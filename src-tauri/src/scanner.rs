@@ -0,0 +1,118 @@
+use std::collections::HashSet;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Bounds that keep a directory scan from running away on huge trees
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanLimits {
+    /// Deepest directory level to descend into (the root is depth 0)
+    pub max_depth: usize,
+    /// Maximum number of directory entries to stat
+    pub max_entries: usize,
+    /// Wall-clock budget for a single scan
+    pub time_budget: Duration,
+}
+
+impl Default for ScanLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 32,
+            max_entries: 200_000,
+            time_budget: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Outcome of a directory scan
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanResult {
+    /// Total size of regular files seen
+    pub bytes: u64,
+    /// Number of entries stat'ed
+    pub entries: usize,
+    /// True if a limit, deadline or cancellation cut the scan short,
+    /// in which case `bytes` is a lower bound
+    pub incomplete: bool,
+}
+
+/// Sum the size of all regular files under `root` within the given limits
+pub fn scan_dir(root: &Path, limits: &ScanLimits, cancel: &AtomicBool) -> ScanResult {
+    walk_dir(root, limits, cancel, |_, _| {})
+}
+
+/// Walk the tree under `root` within the given limits, calling `visit` for
+/// every regular file and every directory that will be descended into
+///
+/// Symlinks are never followed. Directories are remembered by device and
+/// inode, so a bind mount that loops back into the tree is only visited once.
+/// Setting `cancel` stops the walk at the next entry.
+pub fn walk_dir(
+    root: &Path,
+    limits: &ScanLimits,
+    cancel: &AtomicBool,
+    mut visit: impl FnMut(&Path, &Metadata),
+) -> ScanResult {
+    let deadline = Instant::now() + limits.time_budget;
+    let mut result = ScanResult::default();
+    let mut visited = HashSet::new();
+    let mut stack: Vec<(PathBuf, usize)> = vec![(root.to_path_buf(), 0)];
+
+    if let Some(id) = std::fs::metadata(root).ok().as_ref().and_then(dir_id) {
+        visited.insert(id);
+    }
+
+    while let Some((dir, depth)) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            if cancel.load(Ordering::Relaxed)
+                || result.entries >= limits.max_entries
+                || Instant::now() >= deadline
+            {
+                result.incomplete = true;
+                return result;
+            }
+            result.entries += 1;
+
+            // DirEntry::metadata does not traverse symlinks
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+
+            if metadata.is_file() {
+                result.bytes += metadata.len();
+                visit(&entry.path(), &metadata);
+            } else if metadata.is_dir() {
+                if depth + 1 > limits.max_depth {
+                    result.incomplete = true;
+                    continue;
+                }
+                // Skip directories already seen through another path
+                if dir_id(&metadata).is_some_and(|id| !visited.insert(id)) {
+                    continue;
+                }
+                visit(&entry.path(), &metadata);
+                stack.push((entry.path(), depth + 1));
+            }
+        }
+    }
+
+    result
+}
+
+/// Device and inode of a directory, used for cycle detection
+#[cfg(unix)]
+fn dir_id(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+/// Windows has no stable inode through std; symlinks are not followed anyway
+#[cfg(not(unix))]
+fn dir_id(_metadata: &Metadata) -> Option<(u64, u64)> {
+    None
+}
@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// How a metric is smoothed before the pet sees it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Smoothing {
+    /// Use every reading as-is
+    #[default]
+    None,
+    /// Mean of the readings taken within the last `secs` seconds
+    Window { secs: f32 },
+    /// Exponentially weighted moving average; a reading's weight halves
+    /// every `half_life_secs` seconds, independent of the poll interval
+    Ewma { half_life_secs: f32 },
+    /// Median of the last `samples` readings, which ignores isolated spikes
+    Median { samples: usize },
+}
+
+/// Smoothing strategy for each metric
+///
+/// Grouped readings share one strategy but are smoothed separately: each
+/// load average, each PSI average, and each interface's or device's rates.
+/// Byte counts, sizes and temperatures are used as read.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SmoothingConfig {
+    pub ram_percent: Smoothing,
+    pub cpu_percent: Smoothing,
+    pub hottest_core_percent: Smoothing,
+    pub iowait_percent: Smoothing,
+    pub swap_percent: Smoothing,
+    pub swap_in_pages_per_sec: Smoothing,
+    pub swap_out_pages_per_sec: Smoothing,
+    pub disk_junk_percent: Smoothing,
+    /// Raw and per-core load averages
+    pub load_average: Smoothing,
+    /// Pressure stall averages of every resource
+    pub pressure: Smoothing,
+    /// Throughput and fault rates of every network interface
+    pub network: Smoothing,
+    /// Throughput and IOPS of every block device
+    pub disk_io: Smoothing,
+}
+
+impl Default for SmoothingConfig {
+    /// CPU is averaged over 30 seconds, everything else is used raw
+    fn default() -> Self {
+        Self {
+            ram_percent: Smoothing::None,
+            cpu_percent: Smoothing::Window { secs: 30.0 },
+            hottest_core_percent: Smoothing::None,
+            iowait_percent: Smoothing::None,
+            swap_percent: Smoothing::None,
+            swap_in_pages_per_sec: Smoothing::None,
+            swap_out_pages_per_sec: Smoothing::None,
+            disk_junk_percent: Smoothing::None,
+            load_average: Smoothing::None,
+            pressure: Smoothing::None,
+            network: Smoothing::None,
+            disk_io: Smoothing::None,
+        }
+    }
+}
+
+/// Running state for one smoothed metric
+#[derive(Debug, Clone)]
+pub struct Smoother {
+    strategy: Smoothing,
+    samples: VecDeque<(Instant, f32)>,
+    average: Option<(Instant, f32)>,
+}
+
+impl Smoother {
+    pub fn new(strategy: Smoothing) -> Self {
+        Self {
+            strategy,
+            samples: VecDeque::new(),
+            average: None,
+        }
+    }
+
+    /// Feed a reading taken at `at` and return the smoothed value
+    pub fn push(&mut self, at: Instant, value: f32) -> f32 {
+        match self.strategy {
+            Smoothing::None => value,
+            Smoothing::Window { secs } => {
+                self.samples.push_back((at, value));
+                let window = Duration::try_from_secs_f32(secs.max(0.0)).unwrap_or(Duration::MAX);
+                // Always keep the newest reading, however short the window
+                while self.samples.len() > 1
+                    && self
+                        .samples
+                        .front()
+                        .is_some_and(|&(t, _)| at.saturating_duration_since(t) > window)
+                {
+                    self.samples.pop_front();
+                }
+                self.samples.iter().map(|&(_, v)| v).sum::<f32>() / self.samples.len() as f32
+            }
+            Smoothing::Ewma { half_life_secs } => {
+                let smoothed = match self.average {
+                    Some((last_at, last)) if half_life_secs > 0.0 => {
+                        let elapsed = at.saturating_duration_since(last_at).as_secs_f32();
+                        let alpha = 1.0 - 0.5f32.powf(elapsed / half_life_secs);
+                        last + alpha * (value - last)
+                    }
+                    _ => value,
+                };
+                self.average = Some((at, smoothed));
+                smoothed
+            }
+            Smoothing::Median { samples } => {
+                self.samples.push_back((at, value));
+                while self.samples.len() > samples.max(1) {
+                    self.samples.pop_front();
+                }
+                let mut sorted: Vec<f32> = self.samples.iter().map(|&(_, v)| v).collect();
+                sorted.sort_by(|a, b| a.total_cmp(b));
+                let mid = sorted.len() / 2;
+                if sorted.len().is_multiple_of(2) {
+                    (sorted[mid - 1] + sorted[mid]) / 2.0
+                } else {
+                    sorted[mid]
+                }
+            }
+        }
+    }
+}
+
+/// One strategy applied to a group of readings, each smoothed on its own
+///
+/// Readings are told apart by key, such as an interface name and field.
+/// A key not pushed during a sample is forgotten when `end_sample` is called,
+/// so devices that go away do not leave state behind.
+#[derive(Debug, Clone)]
+pub struct KeyedSmoother {
+    strategy: Smoothing,
+    /// Smoother per key, and whether the key was seen this sample
+    smoothers: HashMap<String, (Smoother, bool)>,
+}
+
+impl KeyedSmoother {
+    pub fn new(strategy: Smoothing) -> Self {
+        Self {
+            strategy,
+            smoothers: HashMap::new(),
+        }
+    }
+
+    /// Whether readings pass through unchanged
+    pub fn is_none(&self) -> bool {
+        self.strategy == Smoothing::None
+    }
+
+    /// Feed the reading for `key` taken at `at` and return the smoothed value
+    pub fn push(&mut self, key: &str, at: Instant, value: f32) -> f32 {
+        if self.is_none() {
+            return value;
+        }
+        let (smoother, seen) = self
+            .smoothers
+            .entry(key.to_string())
+            .or_insert_with(|| (Smoother::new(self.strategy), false));
+        *seen = true;
+        smoother.push(at, value)
+    }
+
+    /// Forget keys that were not pushed since the last call
+    pub fn end_sample(&mut self) {
+        self.smoothers.retain(|_, (_, seen)| std::mem::take(seen));
+    }
+}
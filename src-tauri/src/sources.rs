@@ -0,0 +1,1036 @@
+use crate::cgroup::{
+    cgroup_chain, parse_cgroup2_mount, parse_cpu_max, parse_memory_max, parse_self_cgroup,
+    stat_value, EffectiveLimits, LimitSource,
+};
+use crate::diskio::{parse_diskstats, BlockDeviceIo, DeviceCounters};
+use crate::disks::{list_disks, mount_for_path, DiskUsage};
+use crate::junk::{resolve_junk_locations, JunkLocation};
+use crate::junk_index::{JunkTracker, TrackingOptions};
+use crate::load::{parse_loadavg, LoadAverage};
+use crate::monitor::Metrics;
+use crate::network::{parse_net_dev, InterfaceCounters, NetworkUsage};
+use crate::pressure::{parse_pressure, PressureStall};
+use crate::processes::{top_n, ProcessUsage};
+use crate::thermal::{read_thermal_zones, TemperatureReading};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use sysinfo::{CpuRefreshKind, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind, Users};
+
+/// A collector that contributes readings to a metrics snapshot
+///
+/// `SystemMonitor` refreshes every registered source and then lets each one
+/// fill in the fields it knows about, in registration order. A later source
+/// overwrites fields written by an earlier one, so a more precise collector
+/// can be layered on top of a generic one.
+pub trait MetricsSource: Send {
+    /// Refresh underlying data (called from `SystemMonitor::refresh`)
+    fn refresh(&mut self) {}
+
+    /// Write this source's readings into the snapshot
+    fn collect(&mut self, metrics: &mut Metrics);
+}
+
+/// RAM and CPU readings from the cross-platform `sysinfo` crate
+pub struct SysinfoSource {
+    sys: System,
+}
+
+impl SysinfoSource {
+    pub fn new() -> Self {
+        let mut sys = System::new_all();
+        sys.refresh_all();
+
+        Self { sys }
+    }
+}
+
+impl Default for SysinfoSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsSource for SysinfoSource {
+    fn refresh(&mut self) {
+        self.sys.refresh_memory();
+        self.sys.refresh_cpu_all();
+    }
+
+    fn collect(&mut self, metrics: &mut Metrics) {
+        // Available memory counts reclaimable cache as free, unlike `free_memory`
+        let total = self.sys.total_memory() as f64;
+        let used = total - self.sys.available_memory() as f64;
+
+        metrics.ram_percent = if total > 0.0 {
+            ((used / total) * 100.0) as f32
+        } else {
+            0.0
+        };
+        metrics.cpu_percent = self.sys.global_cpu_usage();
+        metrics.per_core_percent = self.sys.cpus().iter().map(|c| c.cpu_usage()).collect();
+
+        // Windows has no load average; sysinfo reports zeros there
+        if !cfg!(windows) {
+            let load = System::load_average();
+            let load = LoadAverage {
+                one: load.one as f32,
+                five: load.five as f32,
+                fifteen: load.fifteen as f32,
+            };
+            metrics.load_per_core = load.per_core(self.sys.cpus().len());
+            metrics.load_average = Some(load);
+        }
+
+        let swap_total = self.sys.total_swap();
+        let swap_used = self.sys.used_swap();
+        metrics.swap_used_mb = swap_used / (1024 * 1024);
+        metrics.swap_percent = if swap_total > 0 {
+            (swap_used as f64 / swap_total as f64 * 100.0) as f32
+        } else {
+            0.0
+        };
+    }
+}
+
+/// RAM, swap, CPU, load, I/O-wait and pressure readings parsed directly from Linux `/proc`
+///
+/// The root directory is configurable so tests can point it at a fixture
+/// tree instead of the live `/proc`.
+pub struct ProcSource {
+    root: PathBuf,
+    last_cpu: Option<CpuTimes>,
+    last_cores: Vec<CpuTimes>,
+    last_swap: Option<(SwapCounters, Instant)>,
+    ram_percent: f32,
+    cpu_percent: f32,
+    per_core_percent: Vec<f32>,
+    load_average: Option<LoadAverage>,
+    iowait_percent: f32,
+    swap_used_mb: u64,
+    swap_percent: f32,
+    swap_in_pages_per_sec: f32,
+    swap_out_pages_per_sec: f32,
+    pressure: PressureStall,
+}
+
+impl ProcSource {
+    /// Read from the live `/proc` filesystem
+    pub fn new() -> Self {
+        Self::with_root("/proc")
+    }
+
+    /// Read `meminfo`, `stat`, `loadavg`, `vmstat` and `pressure/*` from the given directory
+    pub fn with_root(root: impl Into<PathBuf>) -> Self {
+        let mut source = Self {
+            root: root.into(),
+            last_cpu: None,
+            last_cores: Vec::new(),
+            last_swap: None,
+            ram_percent: 0.0,
+            cpu_percent: 0.0,
+            per_core_percent: Vec::new(),
+            load_average: None,
+            iowait_percent: 0.0,
+            swap_used_mb: 0,
+            swap_percent: 0.0,
+            swap_in_pages_per_sec: 0.0,
+            swap_out_pages_per_sec: 0.0,
+            pressure: PressureStall::default(),
+        };
+        // Prime the CPU counters so the first refresh yields a real rate
+        source.refresh();
+        source
+    }
+
+    /// Whether the live `/proc` filesystem is readable on this machine
+    pub fn is_available() -> bool {
+        Path::new("/proc/stat").is_file() && Path::new("/proc/meminfo").is_file()
+    }
+
+    /// Take a sample as of `now`
+    ///
+    /// `refresh` uses the current time; this lets tests control the interval
+    /// that swap rates are computed over.
+    pub fn refresh_at(&mut self, now: Instant) {
+        if let Ok(meminfo) = std::fs::read_to_string(self.root.join("meminfo")) {
+            if let Some(percent) = parse_meminfo_used_percent(&meminfo) {
+                self.ram_percent = percent;
+            }
+            if let Some((used_kb, percent)) = parse_meminfo_swap(&meminfo) {
+                self.swap_used_mb = used_kb / 1024;
+                self.swap_percent = percent;
+            }
+        }
+
+        if let Ok(stat) = std::fs::read_to_string(self.root.join("stat")) {
+            if let Some(times) = parse_cpu_times(&stat) {
+                // CPU usage is a rate, so the first sample only primes the counters
+                if let Some(prev) = self.last_cpu {
+                    self.cpu_percent = times.usage_since(&prev);
+                    self.iowait_percent = times.iowait_since(&prev);
+                }
+                self.last_cpu = Some(times);
+            }
+
+            let cores = parse_core_times(&stat);
+            // A core going offline or online reorders the lines; start over
+            if cores.len() == self.last_cores.len() {
+                self.per_core_percent = cores
+                    .iter()
+                    .zip(&self.last_cores)
+                    .map(|(now, prev)| now.usage_since(prev))
+                    .collect();
+            }
+            self.last_cores = cores;
+        }
+
+        if let Ok(loadavg) = std::fs::read_to_string(self.root.join("loadavg")) {
+            self.load_average = parse_loadavg(&loadavg);
+        }
+
+        if let Ok(vmstat) = std::fs::read_to_string(self.root.join("vmstat")) {
+            if let Some(counters) = parse_vmstat_swap(&vmstat) {
+                if let Some((prev, at)) = self.last_swap {
+                    let secs = now.saturating_duration_since(at).as_secs_f64();
+                    if secs > 0.0 {
+                        let rate = |now: u64, before: u64| {
+                            (now.saturating_sub(before) as f64 / secs) as f32
+                        };
+                        self.swap_in_pages_per_sec = rate(counters.pages_in, prev.pages_in);
+                        self.swap_out_pages_per_sec = rate(counters.pages_out, prev.pages_out);
+                    }
+                }
+                self.last_swap = Some((counters, now));
+            }
+        }
+
+        let read_pressure = |resource: &str| {
+            std::fs::read_to_string(self.root.join("pressure").join(resource))
+                .ok()
+                .and_then(|contents| parse_pressure(&contents))
+        };
+        self.pressure = PressureStall {
+            cpu: read_pressure("cpu"),
+            memory: read_pressure("memory"),
+            io: read_pressure("io"),
+        };
+    }
+}
+
+impl Default for ProcSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsSource for ProcSource {
+    fn refresh(&mut self) {
+        self.refresh_at(Instant::now());
+    }
+
+    fn collect(&mut self, metrics: &mut Metrics) {
+        metrics.ram_percent = self.ram_percent;
+        metrics.cpu_percent = self.cpu_percent;
+        metrics.per_core_percent = self.per_core_percent.clone();
+        metrics.load_average = self.load_average;
+        metrics.load_per_core = self
+            .load_average
+            .and_then(|load| load.per_core(self.last_cores.len()));
+        metrics.iowait_percent = self.iowait_percent;
+        metrics.swap_used_mb = self.swap_used_mb;
+        metrics.swap_percent = self.swap_percent;
+        metrics.swap_in_pages_per_sec = self.swap_in_pages_per_sec;
+        metrics.swap_out_pages_per_sec = self.swap_out_pages_per_sec;
+        metrics.pressure = self.pressure;
+    }
+}
+
+/// Aggregate CPU counters from the first line of `/proc/stat`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CpuTimes {
+    idle: u64,
+    iowait: u64,
+    total: u64,
+}
+
+impl CpuTimes {
+    /// Busy percentage between an earlier sample and this one
+    fn usage_since(&self, prev: &CpuTimes) -> f32 {
+        let total = self.total.saturating_sub(prev.total);
+        let idle = self.idle.saturating_sub(prev.idle);
+
+        if total > 0 {
+            ((total - idle.min(total)) as f64 / total as f64 * 100.0) as f32
+        } else {
+            0.0
+        }
+    }
+
+    /// Percentage of time spent idle with I/O outstanding since an earlier sample
+    fn iowait_since(&self, prev: &CpuTimes) -> f32 {
+        let total = self.total.saturating_sub(prev.total);
+        let iowait = self.iowait.saturating_sub(prev.iowait);
+
+        if total > 0 {
+            (iowait.min(total) as f64 / total as f64 * 100.0) as f32
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Parse the aggregate `cpu` line of `/proc/stat`
+fn parse_cpu_times(stat: &str) -> Option<CpuTimes> {
+    parse_cpu_line(stat.lines().find(|l| l.starts_with("cpu "))?)
+}
+
+/// Parse the per-core `cpuN` lines of `/proc/stat`, in order
+fn parse_core_times(stat: &str) -> Vec<CpuTimes> {
+    stat.lines()
+        .filter(|l| {
+            l.strip_prefix("cpu")
+                .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+        })
+        .filter_map(parse_cpu_line)
+        .collect()
+}
+
+/// Parse one `cpu` line of `/proc/stat`
+///
+/// Guest time is already included in user time, so only the first eight
+/// columns (user..steal) count towards the total.
+fn parse_cpu_line(line: &str) -> Option<CpuTimes> {
+    let fields: Vec<u64> = line
+        .split_whitespace()
+        .skip(1)
+        .take(8)
+        .filter_map(|f| f.parse().ok())
+        .collect();
+
+    if fields.len() < 4 {
+        return None;
+    }
+
+    // idle + iowait
+    let iowait = fields.get(4).copied().unwrap_or(0);
+    let idle = fields[3] + iowait;
+    let total = fields.iter().sum();
+
+    Some(CpuTimes {
+        idle,
+        iowait,
+        total,
+    })
+}
+
+/// Compute used memory percentage from `/proc/meminfo`
+/// Used memory is `MemTotal - MemAvailable`, matching sysinfo on Linux
+fn parse_meminfo_used_percent(meminfo: &str) -> Option<f32> {
+    let total = meminfo_kb(meminfo, "MemTotal").filter(|&t| t > 0)?;
+    let used = total.saturating_sub(meminfo_kb(meminfo, "MemAvailable")?);
+
+    Some((used as f64 / total as f64 * 100.0) as f32)
+}
+
+/// Used swap in kB and as a percentage of `SwapTotal`
+/// A machine without swap reports zero for both
+fn parse_meminfo_swap(meminfo: &str) -> Option<(u64, f32)> {
+    let total = meminfo_kb(meminfo, "SwapTotal")?;
+    let used = total.saturating_sub(meminfo_kb(meminfo, "SwapFree")?);
+
+    let percent = if total > 0 {
+        (used as f64 / total as f64 * 100.0) as f32
+    } else {
+        0.0
+    };
+    Some((used, percent))
+}
+
+/// Value of a `Key:  1234 kB` line in `/proc/meminfo`
+fn meminfo_kb(meminfo: &str, key: &str) -> Option<u64> {
+    meminfo.lines().find_map(|line| {
+        let (name, rest) = line.split_once(':')?;
+        if name != key {
+            return None;
+        }
+        rest.split_whitespace().next()?.parse().ok()
+    })
+}
+
+/// Cumulative pages swapped in and out since boot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SwapCounters {
+    pages_in: u64,
+    pages_out: u64,
+}
+
+/// Parse `pswpin` and `pswpout` from `/proc/vmstat`
+fn parse_vmstat_swap(vmstat: &str) -> Option<SwapCounters> {
+    let mut pages_in = None;
+    let mut pages_out = None;
+
+    for line in vmstat.lines() {
+        let mut parts = line.split_whitespace();
+        let key = parts.next();
+        let value = parts.next().and_then(|v| v.parse::<u64>().ok());
+
+        match key {
+            Some("pswpin") => pages_in = value,
+            Some("pswpout") => pages_out = value,
+            _ => {}
+        }
+    }
+
+    Some(SwapCounters {
+        pages_in: pages_in?,
+        pages_out: pages_out?,
+    })
+}
+
+/// Default sources: `/proc` where available, sysinfo elsewhere, cgroup v2
+/// limits where they apply, plus processes, network, block device I/O (Linux only), temperatures, disks
+/// and junk
+pub fn default_sources() -> Vec<Box<dyn MetricsSource>> {
+    let system: Box<dyn MetricsSource> = if ProcSource::is_available() {
+        Box::new(ProcSource::new())
+    } else {
+        Box::new(SysinfoSource::new())
+    };
+
+    let mut sources: Vec<Box<dyn MetricsSource>> = vec![system];
+    // Layered over the system source so percentages follow container limits
+    if CgroupSource::is_available() {
+        sources.push(Box::new(CgroupSource::new()));
+    }
+    sources.push(Box::new(ProcessSource::new()));
+    sources.push(Box::new(NetworkSource::new()));
+    if DiskIoSource::is_available() {
+        sources.push(Box::new(DiskIoSource::new()));
+    }
+    sources.push(Box::new(ThermalSource::new()));
+    sources.push(Box::new(DiskSource::new()));
+    sources.push(Box::new(JunkSource::from_env()));
+
+    sources
+}
+
+/// RAM and CPU percentages against cgroup v2 limits
+///
+/// Inside a container or a systemd slice with `memory.max` or `cpu.max` set
+/// below the machine's capacity, this overwrites `ram_percent` and
+/// `cpu_percent` with usage relative to the limit and records the source in
+/// `Metrics::limits`. The tightest limit along the cgroup's ancestors wins.
+/// Register it after the system source it corrects.
+pub struct CgroupSource {
+    proc_root: PathBuf,
+    chain: Vec<PathBuf>,
+    group: Option<String>,
+    last_usage: Option<(u64, Instant)>,
+    limits: EffectiveLimits,
+    ram_percent: Option<f32>,
+    cpu_percent: Option<f32>,
+}
+
+impl CgroupSource {
+    /// Read this process's cgroup from the live `/proc` and cgroup2 mount
+    pub fn new() -> Self {
+        let mount = std::fs::read_to_string("/proc/self/mountinfo")
+            .ok()
+            .and_then(|m| parse_cgroup2_mount(&m))
+            .unwrap_or_else(|| PathBuf::from("/sys/fs/cgroup"));
+        Self::with_roots("/proc", mount)
+    }
+
+    /// Read `self/cgroup`, `meminfo` and `stat` under `proc_root`, and
+    /// cgroup files under `cgroup_mount`
+    pub fn with_roots(proc_root: impl Into<PathBuf>, cgroup_mount: impl AsRef<Path>) -> Self {
+        let proc_root = proc_root.into();
+        let group = std::fs::read_to_string(proc_root.join("self/cgroup"))
+            .ok()
+            .and_then(|c| parse_self_cgroup(&c));
+        let chain = group
+            .as_deref()
+            .map(|g| cgroup_chain(cgroup_mount.as_ref(), g))
+            .unwrap_or_default();
+
+        let mut source = Self {
+            proc_root,
+            chain,
+            group,
+            last_usage: None,
+            limits: EffectiveLimits::default(),
+            ram_percent: None,
+            cpu_percent: None,
+        };
+        // Prime the CPU usage counter so the first refresh yields a real rate
+        source.refresh();
+        source
+    }
+
+    /// Whether this process sits in a cgroup v2 hierarchy
+    pub fn is_available() -> bool {
+        std::fs::read_to_string("/proc/self/cgroup")
+            .ok()
+            .and_then(|c| parse_self_cgroup(&c))
+            .is_some()
+    }
+
+    /// Take a sample as of `now`
+    ///
+    /// `refresh` uses the current time; this lets tests control the interval
+    /// that CPU usage is computed over.
+    pub fn refresh_at(&mut self, now: Instant) {
+        let read = |dir: &Path, file: &str| std::fs::read_to_string(dir.join(file)).ok();
+        let Some(group_dir) = self.chain.first() else {
+            return;
+        };
+
+        let host_memory = std::fs::read_to_string(self.proc_root.join("meminfo"))
+            .ok()
+            .and_then(|m| meminfo_kb(&m, "MemTotal"))
+            .map(|kb| kb * 1024);
+        let memory_limit = self
+            .chain
+            .iter()
+            .filter_map(|dir| read(dir, "memory.max").and_then(|c| parse_memory_max(&c)))
+            .min()
+            .filter(|&limit| host_memory.is_some_and(|host| limit < host));
+
+        self.ram_percent = memory_limit.and_then(|limit| {
+            let current = read(group_dir, "memory.current")?
+                .trim()
+                .parse::<u64>()
+                .ok()?;
+            // Page cache the kernel can drop counts as free, as with MemAvailable
+            let inactive_file = read(group_dir, "memory.stat")
+                .and_then(|s| stat_value(&s, "inactive_file"))
+                .unwrap_or(0);
+            let used = current.saturating_sub(inactive_file);
+            Some((used as f64 / limit as f64 * 100.0) as f32)
+        });
+
+        let host_cores = std::fs::read_to_string(self.proc_root.join("stat"))
+            .map(|s| parse_core_times(&s).len())
+            .unwrap_or(0);
+        let cpu_limit = self
+            .chain
+            .iter()
+            .filter_map(|dir| read(dir, "cpu.max").and_then(|c| parse_cpu_max(&c)))
+            .min_by(|a, b| a.total_cmp(b))
+            .filter(|&cores| cores < host_cores as f32);
+
+        let usage = read(group_dir, "cpu.stat").and_then(|s| stat_value(&s, "usage_usec"));
+        self.cpu_percent = match (cpu_limit, usage, self.last_usage) {
+            (Some(cores), Some(usage), Some((prev, at))) => {
+                let elapsed_usec = now.saturating_duration_since(at).as_micros() as f64;
+                (elapsed_usec > 0.0).then(|| {
+                    let used = usage.saturating_sub(prev) as f64;
+                    (used / (elapsed_usec * cores as f64) * 100.0) as f32
+                })
+            }
+            _ => None,
+        };
+        self.last_usage = usage.map(|u| (u, now));
+
+        self.limits = EffectiveLimits {
+            memory_source: if memory_limit.is_some() {
+                LimitSource::Cgroup
+            } else {
+                LimitSource::Host
+            },
+            memory_limit_mb: memory_limit.map(|bytes| bytes / (1024 * 1024)),
+            cpu_source: if cpu_limit.is_some() {
+                LimitSource::Cgroup
+            } else {
+                LimitSource::Host
+            },
+            cpu_limit_cores: cpu_limit,
+            cgroup_path: self.group.clone(),
+        };
+    }
+}
+
+impl Default for CgroupSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsSource for CgroupSource {
+    fn refresh(&mut self) {
+        self.refresh_at(Instant::now());
+    }
+
+    fn collect(&mut self, metrics: &mut Metrics) {
+        if let Some(percent) = self.ram_percent {
+            metrics.ram_percent = percent;
+        }
+        if let Some(percent) = self.cpu_percent {
+            metrics.cpu_percent = percent;
+        }
+        metrics.limits = self.limits.clone();
+    }
+}
+
+/// Top CPU and memory consumers from the sysinfo process table
+pub struct ProcessSource {
+    sys: System,
+    users: Users,
+    limit: usize,
+}
+
+impl ProcessSource {
+    /// Report the top 5 processes for each resource
+    pub fn new() -> Self {
+        Self::with_limit(5)
+    }
+
+    /// Report the top `limit` processes for each resource
+    pub fn with_limit(limit: usize) -> Self {
+        let mut sys = System::new();
+        sys.refresh_cpu_list(CpuRefreshKind::new());
+
+        let mut source = Self {
+            sys,
+            users: Users::new_with_refreshed_list(),
+            limit,
+        };
+        // Prime per-process CPU counters so the first refresh yields a real rate
+        source.refresh();
+        source
+    }
+}
+
+impl Default for ProcessSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsSource for ProcessSource {
+    fn refresh(&mut self) {
+        self.sys.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::new()
+                .with_cpu()
+                .with_memory()
+                .with_user(UpdateKind::OnlyIfNotSet),
+        );
+    }
+
+    fn collect(&mut self, metrics: &mut Metrics) {
+        // sysinfo reports per-process CPU relative to one core
+        let cores = self.sys.cpus().len().max(1) as f32;
+
+        let mut by_cpu = Vec::new();
+        let mut by_memory = Vec::new();
+        // Linux lists threads as tasks too; skip them so a process counts once
+        for process in self
+            .sys
+            .processes()
+            .values()
+            .filter(|p| p.thread_kind().is_none())
+        {
+            let usage = ProcessUsage {
+                pid: process.pid().as_u32(),
+                name: process.name().to_string_lossy().into_owned(),
+                user: process
+                    .user_id()
+                    .and_then(|uid| self.users.get_user_by_id(uid))
+                    .map(|user| user.name().to_string()),
+                value: 0.0,
+            };
+
+            by_cpu.push(ProcessUsage {
+                value: process.cpu_usage() / cores,
+                ..usage.clone()
+            });
+            by_memory.push(ProcessUsage {
+                value: (process.memory() / (1024 * 1024)) as f32,
+                ..usage
+            });
+        }
+
+        metrics.top_cpu = top_n(by_cpu, self.limit);
+        metrics.top_memory = top_n(by_memory, self.limit);
+    }
+}
+
+/// Per-interface network rates and fault counts
+///
+/// Reads `/proc/net/dev` where available, which includes drop counters.
+/// Elsewhere it falls back to `sysinfo::Networks`, which only knows errors.
+pub struct NetworkSource {
+    backend: NetworkBackend,
+    last: HashMap<String, InterfaceCounters>,
+    last_at: Option<Instant>,
+    usages: Vec<NetworkUsage>,
+}
+
+enum NetworkBackend {
+    Proc(PathBuf),
+    Sysinfo(sysinfo::Networks),
+}
+
+impl NetworkSource {
+    /// Read from `/proc/net/dev`, or sysinfo where `/proc` is missing
+    pub fn new() -> Self {
+        if Path::new("/proc/net/dev").is_file() {
+            Self::with_root("/proc")
+        } else {
+            Self::with_backend(NetworkBackend::Sysinfo(
+                sysinfo::Networks::new_with_refreshed_list(),
+            ))
+        }
+    }
+
+    /// Read `net/dev` under the given directory
+    pub fn with_root(root: impl Into<PathBuf>) -> Self {
+        Self::with_backend(NetworkBackend::Proc(root.into()))
+    }
+
+    fn with_backend(backend: NetworkBackend) -> Self {
+        let mut source = Self {
+            backend,
+            last: HashMap::new(),
+            last_at: None,
+            usages: Vec::new(),
+        };
+        // Prime the counters so the first refresh yields a real rate
+        source.refresh();
+        source
+    }
+
+    /// Take a sample as of `now`
+    ///
+    /// `refresh` uses the current time; this lets tests control the interval
+    /// that rates are computed over.
+    pub fn refresh_at(&mut self, now: Instant) {
+        let counters = match &mut self.backend {
+            NetworkBackend::Proc(root) => std::fs::read_to_string(root.join("net/dev"))
+                .map(|contents| parse_net_dev(&contents))
+                .unwrap_or_default(),
+            NetworkBackend::Sysinfo(networks) => {
+                networks.refresh_list();
+                networks
+                    .list()
+                    .iter()
+                    .map(|(name, data)| {
+                        let counters = InterfaceCounters {
+                            rx_bytes: data.total_received(),
+                            tx_bytes: data.total_transmitted(),
+                            rx_errors: data.total_errors_on_received(),
+                            tx_errors: data.total_errors_on_transmitted(),
+                            ..Default::default()
+                        };
+                        (name.clone(), counters)
+                    })
+                    .collect()
+            }
+        };
+
+        // Rates need two samples; interfaces that just appeared report zero
+        let secs = self
+            .last_at
+            .map(|at| now.saturating_duration_since(at).as_secs_f64())
+            .unwrap_or(0.0);
+        self.usages = counters
+            .iter()
+            .map(|(name, now_counters)| {
+                let prev = self.last.get(name).unwrap_or(now_counters);
+                now_counters.usage_since(name, prev, secs)
+            })
+            .collect();
+        self.usages.sort_by(|a, b| a.interface.cmp(&b.interface));
+
+        self.last = counters.into_iter().collect();
+        self.last_at = Some(now);
+    }
+}
+
+impl Default for NetworkSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsSource for NetworkSource {
+    fn refresh(&mut self) {
+        self.refresh_at(Instant::now());
+    }
+
+    fn collect(&mut self, metrics: &mut Metrics) {
+        metrics.networks = self.usages.clone();
+    }
+}
+
+/// Per-block-device throughput and IOPS from Linux `/proc/diskstats`
+pub struct DiskIoSource {
+    root: PathBuf,
+    last: HashMap<String, DeviceCounters>,
+    last_at: Option<Instant>,
+    devices: Vec<BlockDeviceIo>,
+}
+
+impl DiskIoSource {
+    /// Read from the live `/proc` filesystem
+    pub fn new() -> Self {
+        Self::with_root("/proc")
+    }
+
+    /// Read `diskstats` from the given directory
+    pub fn with_root(root: impl Into<PathBuf>) -> Self {
+        let mut source = Self {
+            root: root.into(),
+            last: HashMap::new(),
+            last_at: None,
+            devices: Vec::new(),
+        };
+        // Prime the counters so the first refresh yields a real rate
+        source.refresh();
+        source
+    }
+
+    /// Whether the live `/proc/diskstats` is readable on this machine
+    pub fn is_available() -> bool {
+        Path::new("/proc/diskstats").is_file()
+    }
+
+    /// Take a sample as of `now`
+    ///
+    /// `refresh` uses the current time; this lets tests control the interval
+    /// that rates are computed over.
+    pub fn refresh_at(&mut self, now: Instant) {
+        let counters = std::fs::read_to_string(self.root.join("diskstats"))
+            .map(|contents| parse_diskstats(&contents))
+            .unwrap_or_default();
+
+        // Rates need two samples; devices that just appeared report zero
+        let secs = self
+            .last_at
+            .map(|at| now.saturating_duration_since(at).as_secs_f64())
+            .unwrap_or(0.0);
+        self.devices = counters
+            .iter()
+            .map(|(name, now_counters)| {
+                let prev = self.last.get(name).unwrap_or(now_counters);
+                now_counters.io_since(name, prev, secs)
+            })
+            .collect();
+
+        self.last = counters.into_iter().collect();
+        self.last_at = Some(now);
+    }
+}
+
+impl Default for DiskIoSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsSource for DiskIoSource {
+    fn refresh(&mut self) {
+        self.refresh_at(Instant::now());
+    }
+
+    fn collect(&mut self, metrics: &mut Metrics) {
+        metrics.disk_io = self.devices.clone();
+    }
+}
+
+/// Hardware temperatures and their critical limits
+///
+/// hwmon sensors come from `sysinfo::Components`. On Linux, thermal zones in
+/// `/sys/class/thermal` are added for machines (many ARM boards, some
+/// laptops) that expose no hwmon sensor, skipping zones hwmon already covers.
+/// Sensors that cannot be read are left out, so a machine without any
+/// reports an empty list rather than 0 °C.
+pub struct ThermalSource {
+    components: Option<sysinfo::Components>,
+    zones_root: Option<PathBuf>,
+    readings: Vec<TemperatureReading>,
+}
+
+impl ThermalSource {
+    /// Read hwmon sensors and, where present, `/sys/class/thermal`
+    pub fn new() -> Self {
+        let zones_root = Path::new("/sys/class/thermal");
+        Self::with_backends(
+            Some(sysinfo::Components::new_with_refreshed_list()),
+            zones_root.is_dir().then(|| zones_root.to_path_buf()),
+        )
+    }
+
+    /// Read only the thermal zones under the given directory
+    pub fn with_zones_root(root: impl Into<PathBuf>) -> Self {
+        Self::with_backends(None, Some(root.into()))
+    }
+
+    fn with_backends(components: Option<sysinfo::Components>, zones_root: Option<PathBuf>) -> Self {
+        let mut source = Self {
+            components,
+            zones_root,
+            readings: Vec::new(),
+        };
+        source.refresh();
+        source
+    }
+}
+
+impl Default for ThermalSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsSource for ThermalSource {
+    fn refresh(&mut self) {
+        let mut readings: Vec<TemperatureReading> = Vec::new();
+
+        if let Some(components) = &mut self.components {
+            components.refresh();
+            readings.extend(
+                components.list().iter().filter_map(|c| {
+                    TemperatureReading::new(c.label(), c.temperature(), c.critical())
+                }),
+            );
+        }
+
+        if let Some(root) = &self.zones_root {
+            // hwmon labels start with the driver name, e.g. `acpitz temp1`
+            let zones = read_thermal_zones(root)
+                .into_iter()
+                .filter(|zone| !readings.iter().any(|r| r.label.starts_with(&zone.label)))
+                .collect::<Vec<_>>();
+            readings.extend(zones);
+        }
+
+        self.readings = readings;
+    }
+
+    fn collect(&mut self, metrics: &mut Metrics) {
+        metrics.temperatures = self.readings.clone();
+    }
+}
+
+/// Disk junk readings from temp, cache, trash and crash dump directories
+///
+/// Sizes come from a `JunkTracker`: one background scan per location, then
+/// inotify updates (or periodic rescans where watching is unavailable), so
+/// `collect` never waits on the filesystem. Until a location's first scan
+/// finishes, the snapshot is flagged as incomplete.
+///
+/// The junk percentage is relative to the filesystems that hold the junk,
+/// resolved from `Metrics::disks`, so register this after a `DiskSource`.
+pub struct JunkSource {
+    tracker: JunkTracker,
+}
+
+impl JunkSource {
+    /// Track the locations resolved for this platform
+    pub fn from_env() -> Self {
+        Self::new(resolve_junk_locations())
+    }
+
+    /// Track an explicit set of locations
+    pub fn new(locations: Vec<JunkLocation>) -> Self {
+        Self::with_options(locations, TrackingOptions::default())
+    }
+
+    /// Track an explicit set of locations with custom scan and watch options
+    pub fn with_options(locations: Vec<JunkLocation>, options: TrackingOptions) -> Self {
+        Self {
+            tracker: JunkTracker::new(locations, options),
+        }
+    }
+}
+
+impl MetricsSource for JunkSource {
+    fn refresh(&mut self) {
+        self.tracker.refresh();
+    }
+
+    fn collect(&mut self, metrics: &mut Metrics) {
+        metrics.junk_locations = self.tracker.usages();
+        metrics.junk_scan_incomplete = metrics.junk_locations.iter().any(|u| u.incomplete);
+        metrics.disk_junk_mb = metrics.junk_locations.iter().map(|l| l.size_mb).sum();
+        metrics.total_disk_mb =
+            junk_filesystems_total_mb(&metrics.disks, &self.tracker.locations());
+    }
+}
+
+/// Filesystem inventory from `sysinfo::Disks`
+pub struct DiskSource {
+    disks: sysinfo::Disks,
+}
+
+impl DiskSource {
+    pub fn new() -> Self {
+        Self {
+            disks: sysinfo::Disks::new_with_refreshed_list(),
+        }
+    }
+}
+
+impl Default for DiskSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsSource for DiskSource {
+    fn refresh(&mut self) {
+        // Re-list so newly mounted filesystems show up
+        self.disks.refresh_list();
+    }
+
+    fn collect(&mut self, metrics: &mut Metrics) {
+        metrics.disks = list_disks(&self.disks);
+    }
+}
+
+/// Combined capacity of the filesystems holding the junk locations
+/// Each filesystem counts once even if several locations live on it
+fn junk_filesystems_total_mb(disks: &[DiskUsage], locations: &[JunkLocation]) -> u64 {
+    let mut mounts: Vec<&DiskUsage> = Vec::new();
+    for location in locations {
+        if let Some(disk) = mount_for_path(disks, &location.path) {
+            if !mounts.iter().any(|m| m.mount_point == disk.mount_point) {
+                mounts.push(disk);
+            }
+        }
+    }
+
+    mounts.iter().map(|m| m.total_mb).sum()
+}
+
+/// Fake source that replays a fixed sequence of snapshots
+///
+/// Each `collect` replaces the whole snapshot with the next scripted step.
+/// Once the script runs out, the last step is repeated so a pet can be
+/// held in a steady state.
+pub struct ScriptedSource {
+    steps: VecDeque<Metrics>,
+    last: Metrics,
+}
+
+impl ScriptedSource {
+    pub fn new(steps: impl IntoIterator<Item = Metrics>) -> Self {
+        Self {
+            steps: steps.into_iter().collect(),
+            last: Metrics::default(),
+        }
+    }
+}
+
+impl MetricsSource for ScriptedSource {
+    fn collect(&mut self, metrics: &mut Metrics) {
+        if let Some(step) = self.steps.pop_front() {
+            self.last = step;
+        }
+        *metrics = self.last.clone();
+    }
+}
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Temperature of one hardware sensor, reported in `Metrics`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemperatureReading {
+    pub label: String,
+    pub celsius: f32,
+    /// Temperature at which the hardware shuts down, if the sensor reports one
+    pub critical_celsius: Option<f32>,
+}
+
+impl TemperatureReading {
+    /// Build a reading, dropping values a failed sensor read produces
+    ///
+    /// Drivers report NaN, zero or absurd values when a sensor is absent or
+    /// asleep; those become `None` so they are never mistaken for a cool chip.
+    pub fn new(
+        label: impl Into<String>,
+        celsius: f32,
+        critical_celsius: Option<f32>,
+    ) -> Option<Self> {
+        let plausible = |c: f32| c.is_finite() && c > 0.0 && c < 200.0;
+
+        plausible(celsius).then(|| Self {
+            label: label.into(),
+            celsius,
+            critical_celsius: critical_celsius.filter(|&c| plausible(c)),
+        })
+    }
+}
+
+/// Read ACPI and SoC thermal zones from `<root>/thermal_zone*`
+///
+/// `root` is normally `/sys/class/thermal`. A zone's critical limit comes
+/// from its trip point of type `critical`. Values are in millidegrees.
+pub fn read_thermal_zones(root: &Path) -> Vec<TemperatureReading> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+
+    let mut zones: Vec<_> = entries
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().starts_with("thermal_zone"))
+        .map(|e| e.path())
+        .collect();
+    zones.sort();
+
+    zones
+        .iter()
+        .filter_map(|zone| {
+            let read = |name: &str| std::fs::read_to_string(zone.join(name)).ok();
+            let millidegrees = |name: &str| {
+                read(name)
+                    .and_then(|v| v.trim().parse::<f32>().ok())
+                    .map(|m| m / 1000.0)
+            };
+
+            let label = read("type").map(|t| t.trim().to_string())?;
+            let critical = (0..16)
+                .find(|i| {
+                    read(&format!("trip_point_{}_type", i)).is_some_and(|t| t.trim() == "critical")
+                })
+                .and_then(|i| millidegrees(&format!("trip_point_{}_temp", i)));
+
+            TemperatureReading::new(label, millidegrees("temp")?, critical)
+        })
+        .collect()
+}
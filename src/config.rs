@@ -0,0 +1,184 @@
+use crate::pet::{PetThresholds, Thresholds};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Settings the tray binary reads from the shared `config.toml`
+///
+/// The file is the desktop app's; only the poll interval and the RAM, CPU
+/// and junk settings apply to the tray pet, other keys are ignored here.
+/// Missing keys keep their defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    pub poll_interval_secs: f32,
+    pub metrics: EnabledMetrics,
+    pub thresholds: ThresholdsConfig,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EnabledMetrics {
+    pub ram: bool,
+    pub cpu: bool,
+    pub disk_junk: bool,
+}
+
+/// `[thresholds]` tables in the same layout as the desktop app
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdsConfig {
+    pub memory: PercentLevels,
+    pub cpu: PercentLevels,
+    pub disk: DiskLevels,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PercentLevels {
+    pub percent: Thresholds,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DiskLevels {
+    pub junk_percent: Thresholds,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let pet = PetThresholds::default();
+        let level = |t: Option<Thresholds>| t.expect("default thresholds are all enabled");
+
+        Self {
+            poll_interval_secs: 5.0,
+            metrics: EnabledMetrics {
+                ram: true,
+                cpu: true,
+                disk_junk: true,
+            },
+            thresholds: ThresholdsConfig {
+                memory: PercentLevels {
+                    percent: level(pet.ram_percent),
+                },
+                cpu: PercentLevels {
+                    percent: level(pet.cpu_percent),
+                },
+                disk: DiskLevels {
+                    junk_percent: level(pet.disk_junk_percent),
+                },
+            },
+        }
+    }
+}
+
+impl Config {
+    /// Load the config file at `path`; a missing file means all defaults
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("cannot read {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Parse and validate the contents of a config file
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let user: toml::Table = text.parse().map_err(|e| format!("invalid TOML: {}", e))?;
+        let mut merged = toml::Table::try_from(Self::default()).map_err(|e| e.to_string())?;
+        merge(&mut merged, user);
+
+        let config: Self = toml::Value::Table(merged)
+            .try_into()
+            .map_err(|e| format!("invalid TOML: {}", e))?;
+
+        if !(1.0..=3600.0).contains(&config.poll_interval_secs) {
+            return Err("`poll_interval_secs` must be between 1 and 3600 seconds".to_string());
+        }
+        let t = &config.thresholds;
+        for (key, levels) in [
+            ("thresholds.memory.percent", t.memory.percent),
+            ("thresholds.cpu.percent", t.cpu.percent),
+            ("thresholds.disk.junk_percent", t.disk.junk_percent),
+        ] {
+            let ordered = levels.warning <= levels.stressed && levels.stressed <= levels.critical;
+            if !ordered || !levels.critical.is_finite() {
+                return Err(format!(
+                    "`{}` levels must rise from warning to stressed to critical",
+                    key
+                ));
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Time between samples
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs_f32(self.poll_interval_secs)
+    }
+
+    /// Thresholds for the pet, leaving out disabled metrics
+    pub fn pet_thresholds(&self) -> PetThresholds {
+        let t = &self.thresholds;
+        let m = &self.metrics;
+
+        PetThresholds {
+            ram_percent: m.ram.then_some(t.memory.percent),
+            cpu_percent: m.cpu.then_some(t.cpu.percent),
+            disk_junk_percent: m.disk_junk.then_some(t.disk.junk_percent),
+        }
+    }
+}
+
+/// Layer `user` over `base`, table by table
+fn merge(base: &mut toml::Table, user: toml::Table) {
+    for (key, value) in user {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(user)) => merge(base, user),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// `config.toml` in the per-user config directory, shared with the desktop app
+pub fn config_path() -> Option<PathBuf> {
+    let non_empty = |key: &str| {
+        std::env::var(key)
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+
+    let dir = if cfg!(windows) {
+        non_empty("APPDATA")
+    } else {
+        non_empty("XDG_CONFIG_HOME").or_else(|| non_empty("HOME").map(|h| h.join(".config")))
+    }?;
+    Some(dir.join("sysadmin-tamagotchi").join("config.toml"))
+}
+
+/// Reloads the config file when its modification time or size changes
+pub struct ConfigWatcher {
+    path: PathBuf,
+    seen: Option<(SystemTime, u64)>,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, seen: None }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The reloaded config if the file changed since the last call
+    pub fn poll(&mut self) -> Option<Result<Config, String>> {
+        let current = std::fs::metadata(&self.path)
+            .ok()
+            .and_then(|m| Some((m.modified().ok()?, m.len())));
+        if current == self.seen {
+            return None;
+        }
+        self.seen = current;
+        Some(Config::load(&self.path))
+    }
+}
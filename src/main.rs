@@ -1,26 +1,83 @@
+mod config;
+// Shared with the desktop app, which uses more of them
+#[allow(dead_code)]
+#[path = "../src-tauri/src/junk.rs"]
+mod junk;
 mod monitor;
 mod pet;
+mod save;
+#[allow(dead_code)]
+#[path = "../src-tauri/src/scanner.rs"]
+mod scanner;
+mod sources;
 
+use config::{Config, ConfigWatcher};
 use monitor::SystemMonitor;
 use pet::Pet;
 use tray_icon::{
     menu::{Menu, MenuEvent, MenuItem},
     TrayIconBuilder,
 };
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
 /// Main application state
 struct App {
     monitor: SystemMonitor,
     pet: Pet,
+    config: Option<ConfigWatcher>,
+    save_path: Option<PathBuf>,
+    poll_interval: Duration,
 }
 
 impl App {
     /// Create a new application instance
+    /// The pet resumes in the state recorded in the save file, if any
     fn new() -> Self {
+        let mut pet = Pet::new();
+        let save_path = save::save_path();
+        if let Some(path) = &save_path {
+            match save::load_state(path) {
+                Ok(Some(state)) => pet.resume_state(state),
+                Ok(None) => {}
+                Err(e) => {
+                    eprintln!("Starting with a fresh pet, ignoring {}: {}", path.display(), e)
+                }
+            }
+        }
+
         Self {
             monitor: SystemMonitor::new(),
-            pet: Pet::new(),
+            pet,
+            config: config::config_path().map(ConfigWatcher::new),
+            save_path,
+            poll_interval: Config::default().poll_interval(),
+        }
+    }
+
+    /// Record the pet's state in the save file
+    fn save(&self) {
+        if let Some(path) = &self.save_path {
+            if let Err(e) = save::save_state(path, self.pet.get_state()) {
+                eprintln!("Failed to save {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    /// Apply the config file if it changed since the last check
+    /// An invalid file is reported and the previous settings are kept
+    fn reload_config(&mut self) {
+        let Some(watcher) = self.config.as_mut() else {
+            return;
+        };
+        match watcher.poll() {
+            Some(Ok(config)) => {
+                self.pet.set_thresholds(config.pet_thresholds());
+                self.poll_interval = config.poll_interval();
+                println!("Loaded config from {}", watcher.path().display());
+            }
+            Some(Err(e)) => eprintln!("Ignoring config {}: {}", watcher.path().display(), e),
+            None => {}
         }
     }
 
@@ -72,25 +129,27 @@ fn main() {
 
     // Simple polling loop
     let mut last_poll = Instant::now();
-    let poll_interval = Duration::from_secs(5);
 
     loop {
         // Check for menu events
         if let Ok(event) = menu_channel.try_recv() {
             if event.id == "exit" {
                 println!("Exiting SysAdmin Tamagotchi...");
+                app.save();
                 break;
             }
         }
 
-        // Update pet state every 5 seconds
-        if last_poll.elapsed() >= poll_interval {
+        // Update pet state every 5 seconds (or as configured)
+        if last_poll.elapsed() >= app.poll_interval {
+            app.reload_config();
             let state_changed = app.update();
 
             if state_changed {
                 // In a full implementation, we would update the tray icon here
                 // based on app.pet.get_state()
                 println!("Pet state changed: {}", app.pet.get_icon_description());
+                app.save();
             }
 
             last_poll = Instant::now();
@@ -1,81 +1,61 @@
-use sysinfo::System;
+use crate::sources::{default_sources, MetricsSource};
 use std::time::Instant;
 
 /// System metrics snapshot
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Metrics {
     pub ram_percent: f32,
     pub cpu_percent: f32,
     pub disk_junk_mb: u64,
+    /// Size of the disk the junk lives on
+    pub total_disk_mb: u64,
+    /// Junk as a share of `total_disk_mb`
+    pub disk_junk_percent: f32,
 }
 
-/// System monitor that wraps sysinfo and provides clean metrics API
+/// System monitor that composes metrics sources into a clean metrics API
 pub struct SystemMonitor {
-    sys: System,
+    sources: Vec<Box<dyn MetricsSource>>,
     last_update: Instant,
 }
 
 impl SystemMonitor {
-    /// Create a new system monitor
+    /// Create a new system monitor with the platform's default sources
     pub fn new() -> Self {
-        let mut sys = System::new_all();
-        sys.refresh_all();
+        Self::with_sources(default_sources())
+    }
 
+    /// Create a system monitor from an explicit list of sources
+    /// Sources are collected in order; later sources overwrite earlier fields
+    pub fn with_sources(sources: Vec<Box<dyn MetricsSource>>) -> Self {
         Self {
-            sys,
+            sources,
             last_update: Instant::now(),
         }
     }
 
     /// Refresh system information (call before get_metrics)
     pub fn refresh(&mut self) {
-        self.sys.refresh_memory();
-        self.sys.refresh_cpu_all();
+        for source in &mut self.sources {
+            source.refresh();
+        }
         self.last_update = Instant::now();
     }
 
     /// Get current system metrics
-    pub fn get_metrics(&self) -> Metrics {
-        let ram_percent = self.get_ram_percent();
-        let cpu_percent = self.get_cpu_percent();
-        let disk_junk_mb = self.get_disk_junk_mb();
-
-        Metrics {
-            ram_percent,
-            cpu_percent,
-            disk_junk_mb,
+    pub fn get_metrics(&mut self) -> Metrics {
+        let mut metrics = Metrics::default();
+        for source in &mut self.sources {
+            source.collect(&mut metrics);
         }
-    }
-
-    /// Get RAM usage as percentage (0-100)
-    fn get_ram_percent(&self) -> f32 {
-        let total = self.sys.total_memory() as f64;
-        let used = self.sys.used_memory() as f64;
 
-        if total > 0.0 {
-            ((used / total) * 100.0) as f32
+        metrics.disk_junk_percent = if metrics.total_disk_mb > 0 {
+            (metrics.disk_junk_mb as f32 / metrics.total_disk_mb as f32) * 100.0
         } else {
             0.0
-        }
-    }
+        };
 
-    /// Get CPU usage as percentage (0-100)
-    fn get_cpu_percent(&self) -> f32 {
-        self.sys.global_cpu_usage()
-    }
-
-    /// Get disk junk size in MB (scans Windows TEMP folder)
-    fn get_disk_junk_mb(&self) -> u64 {
-        // Get Windows TEMP directory
-        let temp_dir = std::env::var("TEMP")
-            .or_else(|_| std::env::var("TMP"))
-            .unwrap_or_else(|_| "C:\\Windows\\Temp".to_string());
-
-        // Calculate total size of files in TEMP
-        match calculate_dir_size(&temp_dir) {
-            Ok(bytes) => bytes / (1024 * 1024), // Convert to MB
-            Err(_) => 0, // Return 0 if we can't access TEMP
-        }
+        metrics
     }
 }
 
@@ -84,28 +64,3 @@ impl Default for SystemMonitor {
         Self::new()
     }
 }
-
-/// Calculate total size of a directory in bytes
-fn calculate_dir_size(path: &str) -> std::io::Result<u64> {
-    let mut total_size = 0u64;
-
-    // Use a simple recursive calculation with basic error handling
-    if let Ok(entries) = std::fs::read_dir(path) {
-        for entry in entries.flatten() {
-            if let Ok(metadata) = entry.metadata() {
-                if metadata.is_file() {
-                    total_size += metadata.len();
-                } else if metadata.is_dir() {
-                    // Recursively calculate subdirectories
-                    // Note: This is a simplified version. Could add depth limit
-                    // or timeout for production use to avoid hanging on large dirs
-                    if let Some(path_str) = entry.path().to_str().map(String::from) {
-                        total_size += calculate_dir_size(&path_str).unwrap_or(0);
-                    }
-                }
-            }
-        }
-    }
-
-    Ok(total_size)
-}
@@ -1,7 +1,8 @@
 use crate::monitor::Metrics;
+use serde::{Deserialize, Serialize};
 
 /// Pet state based on system health
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PetState {
     /// All metrics are good
     Happy,
@@ -13,9 +14,48 @@ pub enum PetState {
     Critical,
 }
 
+/// Warning, stressed and critical levels for one metric
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Thresholds {
+    pub warning: f32,
+    pub stressed: f32,
+    pub critical: f32,
+}
+
+impl Thresholds {
+    pub const fn new(warning: f32, stressed: f32, critical: f32) -> Self {
+        Self {
+            warning,
+            stressed,
+            critical,
+        }
+    }
+}
+
+/// Levels used by `Pet::update`; `None` leaves a metric out of the mood
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PetThresholds {
+    pub ram_percent: Option<Thresholds>,
+    pub cpu_percent: Option<Thresholds>,
+    /// Junk as a share of the disk holding it
+    pub disk_junk_percent: Option<Thresholds>,
+}
+
+impl Default for PetThresholds {
+    /// Same defaults as the desktop app
+    fn default() -> Self {
+        Self {
+            ram_percent: Some(Thresholds::new(70.0, 85.0, 95.0)),
+            cpu_percent: Some(Thresholds::new(70.0, 85.0, 95.0)),
+            disk_junk_percent: Some(Thresholds::new(5.0, 10.0, 20.0)),
+        }
+    }
+}
+
 /// Pet that reflects system health
 pub struct Pet {
     state: PetState,
+    thresholds: PetThresholds,
 }
 
 impl Pet {
@@ -23,15 +63,31 @@ impl Pet {
     pub fn new() -> Self {
         Self {
             state: PetState::Happy,
+            thresholds: PetThresholds::default(),
         }
     }
 
+    /// Pick up in `state`, e.g. the one restored from the save file
+    pub fn resume_state(&mut self, state: PetState) {
+        self.state = state;
+    }
+
+    /// Replace the thresholds, keeping the current state
+    pub fn set_thresholds(&mut self, thresholds: PetThresholds) {
+        self.thresholds = thresholds;
+    }
+
     /// Update pet state based on current metrics
     /// Returns the new state
     pub fn update(&mut self, metrics: &Metrics) -> PetState {
-        let ram_status = Self::classify_metric(metrics.ram_percent, 60.0, 80.0, 90.0);
-        let cpu_status = Self::classify_metric(metrics.cpu_percent, 60.0, 80.0, 90.0);
-        let disk_status = Self::classify_disk(metrics.disk_junk_mb);
+        let judge = |value: f32, thresholds: Option<Thresholds>| {
+            thresholds.map_or(MetricStatus::Good, |t| {
+                Self::classify_metric(value, t.warning, t.stressed, t.critical)
+            })
+        };
+        let ram_status = judge(metrics.ram_percent, self.thresholds.ram_percent);
+        let cpu_status = judge(metrics.cpu_percent, self.thresholds.cpu_percent);
+        let disk_status = judge(metrics.disk_junk_percent, self.thresholds.disk_junk_percent);
 
         // Determine overall state based on worst metric
         let statuses = [ram_status, cpu_status, disk_status];
@@ -40,8 +96,10 @@ impl Pet {
         if statuses.iter().any(|&s| s == MetricStatus::Critical) {
             self.state = PetState::Critical;
         }
-        // If 2+ metrics are warning, or 1 warning + 1 critical, pet is stressed
-        else if statuses.iter().filter(|&&s| s != MetricStatus::Good).count() >= 2 {
+        // If any metric is stressed, or 2+ are warning, pet is stressed
+        else if statuses.contains(&MetricStatus::Stressed)
+            || statuses.iter().filter(|&&s| s != MetricStatus::Good).count() >= 2
+        {
             self.state = PetState::Stressed;
         }
         // If one metric is warning, pet is okay
@@ -72,40 +130,22 @@ impl Pet {
     }
 
     /// Classify a metric based on thresholds
-    /// Thresholds match README.md:
-    /// - Happy: <60%
-    /// - Okay: 60-80%
-    /// - Stressed: 80-90%
-    /// - Critical: >90%
+    /// Default thresholds match README.md:
+    /// - Happy: <70%
+    /// - Okay: 70-85%
+    /// - Stressed: 85-95%
+    /// - Critical: ≥95%
     fn classify_metric(value: f32, warning: f32, stressed: f32, critical: f32) -> MetricStatus {
         if value >= critical {
             MetricStatus::Critical
         } else if value >= stressed {
-            MetricStatus::Warning
+            MetricStatus::Stressed
         } else if value >= warning {
             MetricStatus::Warning
         } else {
             MetricStatus::Good
         }
     }
-
-    /// Classify disk junk based on MB thresholds
-    /// Per README.md:
-    /// - Happy: <500MB
-    /// - Okay: 500-2000MB
-    /// - Stressed: 2000-5000MB
-    /// - Critical: >5000MB
-    fn classify_disk(mb: u64) -> MetricStatus {
-        if mb > 5000 {
-            MetricStatus::Critical
-        } else if mb > 2000 {
-            MetricStatus::Warning
-        } else if mb > 500 {
-            MetricStatus::Warning
-        } else {
-            MetricStatus::Good
-        }
-    }
 }
 
 impl Default for Pet {
@@ -119,5 +159,6 @@ impl Default for Pet {
 enum MetricStatus {
     Good,
     Warning,
+    Stressed,
     Critical,
 }
@@ -0,0 +1,100 @@
+use crate::pet::PetState;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Newest save format this binary understands
+const SAVE_VERSION: u32 = 1;
+
+/// What the tray pet keeps between sessions
+///
+/// The tray has its own file rather than sharing the desktop app's
+/// `save.json`, so neither can overwrite the other's newer save.
+#[derive(Debug, Serialize, Deserialize)]
+struct TraySave {
+    version: u32,
+    state: PetState,
+}
+
+/// The state recorded in the tray's save file
+///
+/// An unreadable save falls back to the backup left by the previous
+/// `save_state`. `None` when there is no save yet.
+pub fn load_state(path: &Path) -> Result<Option<PetState>, String> {
+    match read(path) {
+        Ok(state) => Ok(state),
+        Err(e) => match read(&sibling(path, "bak")) {
+            Ok(Some(state)) => Ok(Some(state)),
+            _ => Err(e),
+        },
+    }
+}
+
+fn read(path: &Path) -> Result<Option<PetState>, String> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
+    let save: TraySave = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+    if save.version > SAVE_VERSION {
+        return Err(format!(
+            "save file version {} is newer than this app understands",
+            save.version
+        ));
+    }
+    Ok(Some(save.state))
+}
+
+/// Record `state` in the tray's save file
+///
+/// Written to a temporary file and renamed over the save, so a crash leaves
+/// either the old or the new save. The old save is kept as `<name>.bak`.
+pub fn save_state(path: &Path, state: PetState) -> Result<(), String> {
+    let save = TraySave {
+        version: SAVE_VERSION,
+        state,
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(&save).map_err(|e| e.to_string())?;
+    let temp = sibling(path, "tmp");
+    let mut file = std::fs::File::create(&temp).map_err(|e| e.to_string())?;
+    file.write_all(text.as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(|e| e.to_string())?;
+
+    if path.exists() {
+        std::fs::copy(path, sibling(path, "bak")).map_err(|e| e.to_string())?;
+    }
+    std::fs::rename(&temp, path).map_err(|e| e.to_string())
+}
+
+/// `path` with `suffix` added to its file name, e.g. `tray.json.bak`
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// `tray.json` in the per-user data directory, next to the desktop app's
+/// `save.json`
+pub fn save_path() -> Option<PathBuf> {
+    let non_empty = |key: &str| {
+        std::env::var(key)
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+
+    let dir = if cfg!(windows) {
+        non_empty("APPDATA")
+    } else {
+        non_empty("XDG_DATA_HOME")
+            .or_else(|| non_empty("HOME").map(|h| h.join(".local").join("share")))
+    }?;
+    Some(dir.join("sysadmin-tamagotchi").join("tray.json"))
+}
@@ -0,0 +1,291 @@
+use crate::junk::{resolve_junk_locations, JunkLocation};
+use crate::monitor::Metrics;
+use crate::scanner::{scan_dir, ScanLimits};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+use sysinfo::{Disks, System};
+
+/// How often the junk locations are walked again; the same interval the
+/// desktop app rescans at when it cannot watch them
+const RESCAN_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A collector that contributes readings to a metrics snapshot
+///
+/// `SystemMonitor` refreshes every registered source and then lets each one
+/// fill in the fields it knows about, in registration order. A later source
+/// overwrites fields written by an earlier one, so a more precise collector
+/// can be layered on top of a generic one.
+pub trait MetricsSource: Send {
+    /// Refresh underlying data (called from `SystemMonitor::refresh`)
+    fn refresh(&mut self) {}
+
+    /// Write this source's readings into the snapshot
+    fn collect(&mut self, metrics: &mut Metrics);
+}
+
+/// RAM and CPU readings from the cross-platform `sysinfo` crate
+pub struct SysinfoSource {
+    sys: System,
+}
+
+impl SysinfoSource {
+    pub fn new() -> Self {
+        let mut sys = System::new_all();
+        sys.refresh_all();
+
+        Self { sys }
+    }
+}
+
+impl Default for SysinfoSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsSource for SysinfoSource {
+    fn refresh(&mut self) {
+        self.sys.refresh_memory();
+        self.sys.refresh_cpu_all();
+    }
+
+    fn collect(&mut self, metrics: &mut Metrics) {
+        // Available memory counts reclaimable cache as free, unlike `free_memory`
+        let total = self.sys.total_memory() as f64;
+        let used = total - self.sys.available_memory() as f64;
+
+        metrics.ram_percent = if total > 0.0 {
+            ((used / total) * 100.0) as f32
+        } else {
+            0.0
+        };
+        metrics.cpu_percent = self.sys.global_cpu_usage();
+    }
+}
+
+/// RAM and CPU readings parsed directly from Linux `/proc`
+///
+/// The root directory is configurable so tests can point it at a fixture
+/// tree instead of the live `/proc`.
+pub struct ProcSource {
+    root: PathBuf,
+    last_cpu: Option<CpuTimes>,
+    ram_percent: f32,
+    cpu_percent: f32,
+}
+
+impl ProcSource {
+    /// Read from the live `/proc` filesystem
+    pub fn new() -> Self {
+        Self::with_root("/proc")
+    }
+
+    /// Read `meminfo` and `stat` from the given directory
+    pub fn with_root(root: impl Into<PathBuf>) -> Self {
+        let mut source = Self {
+            root: root.into(),
+            last_cpu: None,
+            ram_percent: 0.0,
+            cpu_percent: 0.0,
+        };
+        // Prime the CPU counters so the first refresh yields a real rate
+        source.refresh();
+        source
+    }
+
+    /// Whether the live `/proc` filesystem is readable on this machine
+    pub fn is_available() -> bool {
+        Path::new("/proc/stat").is_file() && Path::new("/proc/meminfo").is_file()
+    }
+}
+
+impl Default for ProcSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsSource for ProcSource {
+    fn refresh(&mut self) {
+        if let Ok(meminfo) = std::fs::read_to_string(self.root.join("meminfo")) {
+            if let Some(percent) = parse_meminfo_used_percent(&meminfo) {
+                self.ram_percent = percent;
+            }
+        }
+
+        if let Ok(stat) = std::fs::read_to_string(self.root.join("stat")) {
+            if let Some(times) = parse_cpu_times(&stat) {
+                // CPU usage is a rate, so the first sample only primes the counters
+                if let Some(prev) = self.last_cpu {
+                    self.cpu_percent = times.usage_since(&prev);
+                }
+                self.last_cpu = Some(times);
+            }
+        }
+    }
+
+    fn collect(&mut self, metrics: &mut Metrics) {
+        metrics.ram_percent = self.ram_percent;
+        metrics.cpu_percent = self.cpu_percent;
+    }
+}
+
+/// Aggregate CPU counters from the first line of `/proc/stat`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CpuTimes {
+    idle: u64,
+    total: u64,
+}
+
+impl CpuTimes {
+    /// Busy percentage between an earlier sample and this one
+    fn usage_since(&self, prev: &CpuTimes) -> f32 {
+        let total = self.total.saturating_sub(prev.total);
+        let idle = self.idle.saturating_sub(prev.idle);
+
+        if total > 0 {
+            ((total - idle.min(total)) as f64 / total as f64 * 100.0) as f32
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Parse the aggregate `cpu` line of `/proc/stat`
+///
+/// Guest time is already included in user time, so only the first eight
+/// columns (user..steal) count towards the total.
+fn parse_cpu_times(stat: &str) -> Option<CpuTimes> {
+    let line = stat.lines().find(|l| l.starts_with("cpu "))?;
+    let fields: Vec<u64> = line
+        .split_whitespace()
+        .skip(1)
+        .take(8)
+        .filter_map(|f| f.parse().ok())
+        .collect();
+
+    if fields.len() < 4 {
+        return None;
+    }
+
+    // idle + iowait
+    let idle = fields[3] + fields.get(4).copied().unwrap_or(0);
+    let total = fields.iter().sum();
+
+    Some(CpuTimes { idle, total })
+}
+
+/// Compute used memory percentage from `/proc/meminfo`
+/// Used memory is `MemTotal - MemAvailable`, matching sysinfo on Linux
+fn parse_meminfo_used_percent(meminfo: &str) -> Option<f32> {
+    let mut total = None;
+    let mut available = None;
+
+    for line in meminfo.lines() {
+        let mut parts = line.split_whitespace();
+        let key = parts.next();
+        let value = parts.next().and_then(|v| v.parse::<u64>().ok());
+
+        match key {
+            Some("MemTotal:") => total = value,
+            Some("MemAvailable:") => available = value,
+            _ => {}
+        }
+    }
+
+    let total = total.filter(|&t| t > 0)?;
+    let used = total.saturating_sub(available?);
+
+    Some((used as f64 / total as f64 * 100.0) as f32)
+}
+
+/// Default sources: `/proc` where available, sysinfo elsewhere, plus TEMP
+pub fn default_sources() -> Vec<Box<dyn MetricsSource>> {
+    let system: Box<dyn MetricsSource> = if ProcSource::is_available() {
+        Box::new(ProcSource::new())
+    } else {
+        Box::new(SysinfoSource::new())
+    };
+
+    vec![system, Box::new(JunkSource::from_env())]
+}
+
+/// Disk junk readings from bounded scans of the locations the desktop app
+/// measures and cleans
+pub struct JunkSource {
+    locations: Vec<JunkLocation>,
+    /// Size of the filesystems holding the locations, looked up once
+    total_disk_mb: u64,
+    junk_mb: u64,
+    last_scan: Option<Instant>,
+}
+
+impl JunkSource {
+    /// Scan the locations resolved for this platform
+    pub fn from_env() -> Self {
+        Self::new(resolve_junk_locations())
+    }
+
+    /// Scan an explicit set of locations
+    pub fn new(locations: Vec<JunkLocation>) -> Self {
+        let total_disk_mb = filesystems_size_for(&locations) / (1024 * 1024);
+
+        Self {
+            locations,
+            total_disk_mb,
+            junk_mb: 0,
+            last_scan: None,
+        }
+    }
+}
+
+impl MetricsSource for JunkSource {
+    fn refresh(&mut self) {
+        if self
+            .last_scan
+            .is_some_and(|at| at.elapsed() < RESCAN_INTERVAL)
+        {
+            return;
+        }
+
+        // Each walk stops at the scan limits, so a huge cache cannot stall
+        // the tray
+        let cancel = AtomicBool::new(false);
+        let junk_bytes: u64 = self
+            .locations
+            .iter()
+            .map(|location| scan_dir(&location.path, &ScanLimits::default(), &cancel).bytes)
+            .sum();
+        self.junk_mb = junk_bytes / (1024 * 1024);
+        self.last_scan = Some(Instant::now());
+    }
+
+    fn collect(&mut self, metrics: &mut Metrics) {
+        metrics.disk_junk_mb = self.junk_mb;
+        metrics.total_disk_mb = self.total_disk_mb;
+    }
+}
+
+/// Total size in bytes of the filesystems `locations` live on, each counted
+/// once; 0 if unknown
+fn filesystems_size_for(locations: &[JunkLocation]) -> u64 {
+    let disks = Disks::new_with_refreshed_list();
+    let mut mounts: Vec<&Path> = Vec::new();
+    let mut total = 0;
+
+    for location in locations {
+        let disk = disks
+            .iter()
+            .filter(|d| location.path.starts_with(d.mount_point()))
+            .max_by_key(|d| d.mount_point().as_os_str().len());
+        if let Some(disk) = disk {
+            if !mounts.contains(&disk.mount_point()) {
+                mounts.push(disk.mount_point());
+                total += disk.total_space();
+            }
+        }
+    }
+
+    total
+}
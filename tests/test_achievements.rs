@@ -0,0 +1,149 @@
+// Integration tests for achievements
+// Tests unlocking, critical-free streaks, quick recoveries, progress and saves
+
+use std::time::{Duration, Instant, SystemTime};
+use sysadmin_tamagotchi::{
+    AchievementId, AchievementTracker, Life, LifeStage, Metrics, Pet, PetState, PetStats, SaveData,
+};
+
+const MINUTE: Duration = Duration::from_secs(60);
+const DAY: Duration = Duration::from_secs(24 * 3600);
+
+#[test]
+fn test_calm_week_and_quick_recovery() {
+    let start = Instant::now();
+    let stats = PetStats::default();
+    let life = Life::default();
+    let mut tracker = AchievementTracker::default();
+
+    // Six calm days, then a slow recovery: the streak restarts, nothing earned
+    tracker.update_at(PetState::Happy, stats, &life, start);
+    tracker.update_at(PetState::Stressed, stats, &life, start + 6 * DAY);
+    assert_eq!(tracker.achievements().secs_without_critical, 518_400.0);
+    let mut now = start + 6 * DAY;
+    tracker.update_at(PetState::Critical, stats, &life, now);
+    assert_eq!(tracker.achievements().secs_without_critical, 0.0);
+    now += 10 * MINUTE;
+    tracker.update_at(PetState::Okay, stats, &life, now);
+    assert!(tracker.take_recent().is_empty());
+
+    // Critical time never counts towards the streak
+    assert_eq!(tracker.achievements().secs_without_critical, 0.0);
+
+    // Fixed within five minutes
+    tracker.update_at(PetState::Critical, stats, &life, now);
+    now += 4 * MINUTE;
+    tracker.update_at(PetState::Happy, stats, &life, now);
+    assert_eq!(tracker.take_recent(), [AchievementId::QuickRecovery]);
+
+    // A full week without going Critical
+    tracker.update_at(PetState::Okay, stats, &life, now + 7 * DAY);
+    assert_eq!(tracker.take_recent(), [AchievementId::CalmWeek]);
+
+    // Each is announced once
+    tracker.update_at(PetState::Happy, stats, &life, now + 8 * DAY);
+    assert!(tracker.take_recent().is_empty());
+    assert!(tracker.achievements().is_unlocked(AchievementId::CalmWeek));
+
+    println!("Streak and recovery test passed");
+}
+
+#[test]
+fn test_cleanups_and_growth_unlock_achievements() {
+    let start = Instant::now();
+    let life = Life::default();
+    let mut tracker = AchievementTracker::default();
+
+    tracker.update_at(PetState::Happy, PetStats::default(), &life, start);
+    assert!(tracker.take_recent().is_empty());
+
+    let stats = PetStats {
+        cleanups: 3,
+        cleaned_mb: 5 * 1024,
+        ..Default::default()
+    };
+    tracker.update_at(PetState::Happy, stats, &life, start);
+    assert_eq!(tracker.take_recent(), [AchievementId::FirstCleanup]);
+    let progress = tracker
+        .achievements()
+        .progress(AchievementId::SpringCleaning, stats, &life);
+    assert_eq!(progress, 0.5);
+
+    let stats = PetStats {
+        cleanups: 4,
+        cleaned_mb: 10 * 1024,
+        ..Default::default()
+    };
+    let adult = Life {
+        stage: LifeStage::Adult,
+        ..Default::default()
+    };
+    tracker.update_at(PetState::Happy, stats, &adult, start);
+    assert_eq!(
+        tracker.take_recent(),
+        [AchievementId::SpringCleaning, AchievementId::Hatched]
+    );
+    let elder = tracker
+        .achievements()
+        .progress(AchievementId::Elder, stats, &adult);
+    assert_eq!(elder, 0.75);
+
+    println!("Cleanup and growth achievements test passed");
+}
+
+#[test]
+fn test_pet_announces_and_saves_achievements() {
+    let start = Instant::now();
+    let idle = Metrics {
+        ram_percent: 30.0,
+        cpu_percent: 10.0,
+        ..Default::default()
+    };
+    let mut pet = Pet::new();
+    pet.update_at(&idle, start);
+    assert!(pet.new_achievements().is_empty());
+
+    // Unlocked on the sample after the cleanup, then announced only once
+    pet.feed(200);
+    pet.update_at(&idle, start + MINUTE);
+    let unlocked = pet.new_achievements();
+    assert_eq!(unlocked.len(), 1);
+    assert_eq!(unlocked[0].id, AchievementId::FirstCleanup);
+    assert_eq!(unlocked[0].name, "Tidy Up");
+    assert!(unlocked[0].unlocked_at_unix_secs.is_some());
+    pet.update_at(&idle, start + 2 * MINUTE);
+    assert!(pet.new_achievements().is_empty());
+
+    // Unlocked by an update nobody announced, e.g. a command's; the next
+    // sample that is announced still reports it
+    let mut busy = Pet::new();
+    busy.update_at(&idle, start);
+    busy.feed(200);
+    busy.update_at(&idle, start + MINUTE);
+    busy.update_at(&idle, start + 2 * MINUTE);
+    let unlocked = busy.new_achievements();
+    assert_eq!(unlocked.len(), 1);
+    assert_eq!(unlocked[0].id, AchievementId::FirstCleanup);
+
+    let listed = pet.list_achievements();
+    assert_eq!(listed.len(), AchievementId::ALL.len());
+    assert_eq!(
+        listed.iter().filter(|a| a.progress == 1.0).count(),
+        1,
+        "{:?}",
+        listed
+    );
+
+    // Achievements survive a restart and are not announced again
+    let saved = SaveData::capture(&pet, SystemTime::now());
+    let text = serde_json::to_string(&saved).unwrap();
+    let mut restored = Pet::new();
+    SaveData::parse(&text)
+        .unwrap()
+        .restore_into(&mut restored, SystemTime::now());
+    assert_eq!(restored.achievements(), pet.achievements());
+    restored.update_at(&idle, start);
+    assert!(restored.new_achievements().is_empty());
+
+    println!("Pet achievements test passed");
+}
@@ -0,0 +1,222 @@
+// Integration tests for the user config file
+// Tests parsing, validation, hot reload and applying settings
+
+use sysadmin_tamagotchi::config::config_path_with;
+use sysadmin_tamagotchi::{
+    CleanupSummary, Config, ConfigError, ConfigWatcher, JunkKind, Metrics, Pet, PetState,
+    ScriptedSource, Smoothing, SystemMonitor,
+};
+
+#[test]
+fn test_config_layers_over_defaults() {
+    assert_eq!(Config::parse("").unwrap(), Config::default());
+
+    let config = Config::parse(
+        r#"
+        poll_interval_secs = 10
+
+        [thresholds.cpu.percent]
+        warning = 60
+
+        [smoothing.ram_percent]
+        kind = "ewma"
+        half_life_secs = 20
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(config.poll_interval_secs, 10.0);
+    let cpu = config.thresholds.cpu.percent;
+    assert_eq!(cpu.warning, 60.0);
+    // Unset keys keep the app defaults, including hysteresis and dwell times
+    assert_eq!(cpu.stressed, 85.0);
+    assert_eq!(
+        cpu.hysteresis,
+        Config::default().thresholds.cpu.percent.hysteresis
+    );
+    assert_eq!(config.thresholds.dwell, Config::default().thresholds.dwell);
+    assert_eq!(
+        config.smoothing.ram_percent,
+        Smoothing::Ewma {
+            half_life_secs: 20.0
+        }
+    );
+    assert_eq!(
+        config.smoothing.cpu_percent,
+        Config::default().smoothing.cpu_percent
+    );
+
+    println!("Config layering test passed: {:?}", config.thresholds.cpu);
+}
+
+#[test]
+fn test_config_errors_name_the_problem() {
+    let error = |text: &str| Config::parse(text).unwrap_err().to_string();
+
+    // Typos are rejected rather than silently ignored
+    let typo = error("[thresholds.cpu.percent]\nwarnig = 60\n");
+    assert!(typo.contains("warnig"), "{}", typo);
+
+    let syntax = error("poll_interval_secs = \n");
+    assert!(syntax.starts_with("invalid TOML"), "{}", syntax);
+
+    let unordered =
+        error("[thresholds.memory.percent]\nwarning = 90\nstressed = 80\ncritical = 95\n");
+    assert!(
+        unordered.contains("thresholds.memory.percent"),
+        "{}",
+        unordered
+    );
+    assert!(unordered.contains("must rise"), "{}", unordered);
+
+    assert!(matches!(
+        Config::parse("poll_interval_secs = 0.1"),
+        Err(ConfigError::Invalid { key, .. }) if key == "poll_interval_secs"
+    ));
+    let negative_margin = error(
+        r#"
+        [thresholds.io.iowait_percent]
+        warning = 10
+        stressed = 20
+        critical = 30
+        hysteresis = -1
+        "#,
+    );
+    assert!(
+        negative_margin.contains("hysteresis"),
+        "{}",
+        negative_margin
+    );
+    let dwell = error("[thresholds.dwell]\nokay_secs = -5\n");
+    assert!(dwell.contains("thresholds.dwell.okay_secs"), "{}", dwell);
+    let weight = error("[weights]\nram = -1\n");
+    assert!(weight.contains("weights.ram"), "{}", weight);
+    let median = error("[smoothing.cpu_percent]\nkind = \"median\"\nsamples = 0\n");
+    assert!(median.contains("smoothing.cpu_percent"), "{}", median);
+
+    // Thermal margins fall rather than rise
+    assert!(Config::parse(
+        "[thresholds.thermal.below_critical_celsius]\nwarning = 15\nstressed = 8\ncritical = 3\n"
+    )
+    .is_ok());
+
+    println!("Config error test passed: {}", unordered);
+}
+
+#[test]
+fn test_config_watcher_reloads_on_change() {
+    let root = std::env::temp_dir().join(format!("tamagotchi-config-{}", std::process::id()));
+    std::fs::remove_dir_all(&root).ok();
+    std::fs::create_dir_all(&root).unwrap();
+    let path = root.join("config.toml");
+
+    // No file: nothing to report, the defaults stay in place
+    let mut watcher = ConfigWatcher::new(&path);
+    assert!(watcher.poll().is_none());
+
+    std::fs::write(&path, "poll_interval_secs = 2\n").unwrap();
+    let config = watcher.poll().unwrap().unwrap();
+    assert_eq!(config.poll_interval_secs, 2.0);
+    assert!(watcher.poll().is_none());
+
+    // A broken edit is reported once; the caller keeps the last good config
+    std::fs::write(&path, "poll_interval_secs = \"often\"\n").unwrap();
+    assert!(watcher.poll().unwrap().is_err());
+    assert!(watcher.poll().is_none());
+
+    // Removing the file reverts to the defaults
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(watcher.poll().unwrap().unwrap(), Config::default());
+
+    std::fs::remove_dir_all(&root).ok();
+    println!("Config watcher test passed");
+}
+
+#[test]
+fn test_config_applies_to_monitor_and_pet() {
+    let config = Config::parse(
+        r#"
+        [metrics]
+        ram = false
+
+        [smoothing.cpu_percent]
+        kind = "none"
+
+        [cleanup]
+        empty_trash = true
+        "#,
+    )
+    .unwrap();
+
+    let swamped = Metrics {
+        ram_percent: 97.0,
+        cpu_percent: 20.0,
+        ..Default::default()
+    };
+    let mut monitor = SystemMonitor::with_sources(vec![Box::new(ScriptedSource::new([swamped]))]);
+    let mut pet = Pet::new();
+    config.apply(&mut monitor, &mut pet);
+
+    assert_eq!(monitor.smoothing(), &config.smoothing);
+    assert!(monitor.cleanup_options().allows(JunkKind::Trash));
+    // RAM is still reported, it just no longer worries the pet
+    let metrics = monitor.get_metrics();
+    assert_eq!(metrics.ram_percent, 97.0);
+    assert_eq!(pet.update(&metrics), PetState::Happy);
+
+    Config::default().apply(&mut monitor, &mut pet);
+    assert_eq!(pet.update(&metrics), PetState::Critical);
+    // Emptying the trash is permanent, so it is off unless asked for
+    assert!(!monitor.cleanup_options().allows(JunkKind::Trash));
+    assert!(monitor.cleanup_options().allows(JunkKind::Cache));
+
+    println!("Config apply test passed");
+}
+
+#[test]
+fn test_cleanup_message_names_what_was_deleted() {
+    let summary = |kinds: Vec<JunkKind>| CleanupSummary {
+        deleted_mb: 12,
+        kinds,
+    };
+    assert_eq!(
+        summary(vec![JunkKind::Temp]).message(),
+        "Freed 12 MB of temporary files"
+    );
+    assert_eq!(
+        summary(vec![JunkKind::Temp, JunkKind::Cache, JunkKind::Trash]).message(),
+        "Freed 12 MB of temporary files, caches and the trash"
+    );
+
+    println!("Cleanup message test passed");
+}
+
+#[test]
+fn test_config_path_uses_xdg_config_home() {
+    let env = |vars: &'static [(&'static str, &'static str)]| {
+        move |key: &str| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.to_string())
+        }
+    };
+
+    if cfg!(windows) {
+        let path = config_path_with(env(&[("APPDATA", r"C:\Users\me\AppData\Roaming")])).unwrap();
+        assert!(path.ends_with("sysadmin-tamagotchi/config.toml"));
+    } else {
+        let path = config_path_with(env(&[("XDG_CONFIG_HOME", "/cfg"), ("HOME", "/home/me")]));
+        assert_eq!(
+            path.unwrap(),
+            std::path::Path::new("/cfg/sysadmin-tamagotchi/config.toml")
+        );
+        let path = config_path_with(env(&[("XDG_CONFIG_HOME", ""), ("HOME", "/home/me")]));
+        assert_eq!(
+            path.unwrap(),
+            std::path::Path::new("/home/me/.config/sysadmin-tamagotchi/config.toml")
+        );
+    }
+    assert!(config_path_with(env(&[])).is_none());
+
+    println!("Config path test passed");
+}
@@ -0,0 +1,171 @@
+// Integration tests for sickness and death
+// Tests falling sick, recovery, death, the graveyard, revival, saves and config
+
+use std::time::{Duration, Instant, SystemTime};
+use sysadmin_tamagotchi::{
+    Cause, Condition, Config, IllnessRules, IllnessTracker, LifeStage, Metrics, Needs, Pet,
+    PetState, SaveData,
+};
+
+const MINUTE: Duration = Duration::from_secs(60);
+const HOUR: Duration = Duration::from_secs(3600);
+
+/// A machine with RAM nearly full
+fn overloaded() -> Metrics {
+    Metrics {
+        ram_percent: 99.0,
+        cpu_percent: 10.0,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_sickness_and_recovery() {
+    let start = Instant::now();
+    let fed = Needs::default();
+    let mut tracker = IllnessTracker::default();
+
+    // Half an hour Critical without a break makes the pet sick
+    assert!(tracker.update_at(PetState::Critical, fed, start).is_none());
+    assert!(tracker
+        .update_at(PetState::Happy, fed, start + 20 * MINUTE)
+        .is_none());
+    tracker.update_at(PetState::Critical, fed, start + 21 * MINUTE);
+    assert!(tracker
+        .update_at(PetState::Critical, fed, start + 50 * MINUTE)
+        .is_none());
+    let sick = tracker
+        .update_at(PetState::Critical, fed, start + 51 * MINUTE)
+        .unwrap();
+    assert_eq!((sick.from, sick.to), (Condition::Well, Condition::Sick));
+    assert_eq!(tracker.illness().cause, Some(Cause::Critical));
+    assert_eq!(
+        tracker.illness().shown_state(PetState::Happy),
+        PetState::Sick
+    );
+
+    // Ten minutes Okay or better cures it
+    tracker.update_at(PetState::Okay, fed, start + 60 * MINUTE);
+    assert!(tracker
+        .update_at(PetState::Okay, fed, start + 65 * MINUTE)
+        .is_none());
+    let cured = tracker
+        .update_at(PetState::Happy, fed, start + 70 * MINUTE)
+        .unwrap();
+    assert_eq!(cured.to, Condition::Well);
+    assert_eq!(tracker.illness().cause, None);
+    assert_eq!(tracker.illness().sick_secs, 0.0);
+
+    // Six hours with a need neglected does too, even on a healthy system
+    let starving = Needs {
+        hunger: 5.0,
+        ..Needs::default()
+    };
+    let mut now = start + 2 * HOUR;
+    tracker.update_at(PetState::Happy, starving, now);
+    now += 6 * HOUR;
+    tracker.update_at(PetState::Happy, starving, now).unwrap();
+    assert_eq!(tracker.illness().cause, Some(Cause::Neglect));
+    // Neglect also holds back recovery, but a cleanup cures it at once
+    tracker.update_at(PetState::Happy, starving, now + HOUR);
+    assert_eq!(tracker.illness().condition, Condition::Sick);
+    assert_eq!(tracker.treat().unwrap().reason, "treated with a cleanup");
+    assert!(tracker.treat().is_none());
+
+    println!("Sickness test passed");
+}
+
+#[test]
+fn test_death_graveyard_and_revival() {
+    let start = Instant::now();
+    let mut pet = Pet::new();
+    pet.set_illness_rules(IllnessRules {
+        sick_after_critical_secs: 600.0,
+        dead_after_sick_secs: 3600.0,
+        ..Default::default()
+    });
+
+    // Left overloaded, the pet falls sick and then dies
+    let mut now = start;
+    while pet.get_state() != PetState::Sick {
+        pet.update_at(&overloaded(), now);
+        now += MINUTE;
+        assert!(now < start + HOUR, "never fell sick");
+    }
+    assert_eq!(pet.system_state(), PetState::Critical);
+    assert_eq!(pet.get_emoji(), "🤒");
+    assert_eq!(pet.last_transition().unwrap().to, PetState::Sick);
+    while pet.update_at(&overloaded(), now) != PetState::Dead {
+        now += MINUTE;
+        assert!(now < start + 3 * HOUR, "never died");
+    }
+    assert_eq!(pet.last_transition().unwrap().reason, "sick for too long");
+    let grave = pet.illness().graveyard.last().unwrap().clone();
+    assert_eq!(grave.cause, Cause::Critical);
+    assert!(grave.lifetime_secs > 0.0);
+
+    // A dead pet stays dead while the system stays overloaded and does not age
+    let age = pet.life().age_secs;
+    pet.update_at(&overloaded(), now + HOUR);
+    assert_eq!(pet.get_state(), PetState::Dead);
+    assert_eq!(pet.life().age_secs, age);
+
+    // A cleanup hatches a new pet; the graveyard remembers the old one
+    pet.feed(100);
+    assert_eq!(pet.get_state(), PetState::Critical);
+    assert_eq!(pet.life().stage, LifeStage::Egg);
+    assert_eq!(pet.life().age_secs, 0.0);
+    assert_eq!(pet.progress().level, 1);
+    assert_eq!(pet.needs(), Needs::default());
+    assert_eq!(pet.illness().graveyard, [grave]);
+    assert_eq!(
+        pet.last_transition().unwrap().reason,
+        "a cleanup hatched a new pet"
+    );
+
+    println!("Death test passed: {:?}", pet.illness());
+}
+
+#[test]
+fn test_illness_survives_a_restart_and_is_configurable() {
+    let start = Instant::now();
+    let mut pet = Pet::new();
+    pet.set_illness_rules(IllnessRules {
+        sick_after_critical_secs: 0.0,
+        ..Default::default()
+    });
+    let mut now = start;
+    while pet.update_at(&overloaded(), now) != PetState::Sick {
+        now += MINUTE;
+        assert!(now < start + HOUR, "never fell sick");
+    }
+
+    // The save keeps the system's own state; the illness comes back on its own
+    let saved = SaveData::capture(&pet, SystemTime::now());
+    assert_eq!(saved.state, PetState::Critical);
+    let text = serde_json::to_string(&saved).unwrap();
+    let mut restored = Pet::new();
+    SaveData::parse(&text)
+        .unwrap()
+        .restore_into(&mut restored, SystemTime::now());
+    assert_eq!(restored.illness(), pet.illness());
+    assert_eq!(restored.get_state(), PetState::Sick);
+
+    let config = Config::parse(
+        r#"
+        [illness]
+        sick_after_critical_secs = 900
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.illness.sick_after_critical_secs, 900.0);
+    assert_eq!(
+        config.illness.recovery_secs,
+        IllnessRules::default().recovery_secs
+    );
+    let error = |text: &str| Config::parse(text).unwrap_err().to_string();
+    assert!(error("[illness]\ndead_after_sick_secs = -1").contains("illness.dead_after_sick_secs"));
+    assert!(error("[illness]\nneglected_below = 150").contains("between 0 and 100"));
+
+    println!("Illness save and config test passed");
+}
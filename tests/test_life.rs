@@ -0,0 +1,115 @@
+// Integration tests for life stages
+// Tests hatching, care from critical episodes, evolution history and saves
+
+use std::time::{Duration, Instant};
+use sysadmin_tamagotchi::{LifeStage, LifeTracker, Metrics, Pet, PetState, SaveData};
+
+const MINUTE: Duration = Duration::from_secs(60);
+const HOUR: Duration = Duration::from_secs(3600);
+
+#[test]
+fn test_egg_hatches_after_healthy_uptime() {
+    let start = Instant::now();
+    let mut life = LifeTracker::default();
+    assert_eq!(life.life().stage, LifeStage::Egg);
+
+    // Time spent stressed or critical does not count
+    assert!(life.update_at(PetState::Stressed, start).is_none());
+    assert!(life
+        .update_at(PetState::Happy, start + 20 * MINUTE)
+        .is_none());
+    assert_eq!(life.life().healthy_uptime_secs, 0.0);
+
+    assert!(life
+        .update_at(PetState::Okay, start + 29 * MINUTE)
+        .is_none());
+    let hatched = life
+        .update_at(PetState::Happy, start + 30 * MINUTE)
+        .unwrap();
+    assert_eq!(hatched.from, LifeStage::Egg);
+    assert_eq!(hatched.to, LifeStage::Baby);
+    assert_eq!(hatched.healthy_uptime_secs, 600.0);
+    assert_eq!(life.life().history, vec![hatched]);
+
+    println!("Hatching test passed");
+}
+
+#[test]
+fn test_neglect_holds_back_evolution() {
+    let start = Instant::now();
+    let mut life = LifeTracker::default();
+    life.update_at(PetState::Happy, start);
+    // One stage per update, even when the uptime would allow more
+    life.update_at(PetState::Happy, start + 3 * HOUR);
+    assert_eq!(life.life().stage, LifeStage::Baby);
+    life.update_at(PetState::Happy, start + 3 * HOUR);
+    assert_eq!(life.life().stage, LifeStage::Teen);
+    assert_eq!(life.life().history.len(), 2);
+
+    // A critical state fixed within five minutes keeps full care
+    let mut now = start + 3 * HOUR;
+    life.update_at(PetState::Critical, now);
+    now += 4 * MINUTE;
+    life.update_at(PetState::Happy, now);
+    assert_eq!(life.life().critical_episodes, 1);
+    assert_eq!(life.life().care(), 100.0);
+
+    // Two left alone for an hour drag care down to a third
+    for _ in 0..2 {
+        life.update_at(PetState::Critical, now);
+        now += 2 * HOUR;
+        life.update_at(PetState::Okay, now);
+    }
+    assert_eq!(life.life().critical_episodes, 3);
+    assert!((life.life().care() - 100.0 / 3.0).abs() < 0.01);
+
+    // Plenty of healthy uptime, but not enough care to become an adult
+    now += 24 * HOUR;
+    assert!(life.update_at(PetState::Happy, now).is_none());
+    assert_eq!(life.life().stage, LifeStage::Teen);
+
+    // Prompt care from here on earns it back
+    for _ in 0..6 {
+        life.update_at(PetState::Critical, now);
+        now += MINUTE;
+        life.update_at(PetState::Happy, now);
+    }
+    assert_eq!(life.life().stage, LifeStage::Adult);
+
+    println!("Care test passed: {}", life.life().care());
+}
+
+#[test]
+fn test_pet_grows_up_and_remembers() {
+    let start = Instant::now();
+    let calm = Metrics {
+        ram_percent: 30.0,
+        cpu_percent: 10.0,
+        ..Default::default()
+    };
+
+    let mut pet = Pet::new();
+    assert_eq!(pet.stage(), LifeStage::Egg);
+    assert!(pet.last_evolution().is_none());
+    pet.update_at(&calm, start);
+    pet.update_at(&calm, start + 15 * MINUTE);
+    assert_eq!(pet.stage(), LifeStage::Baby);
+    assert_eq!(pet.last_evolution().unwrap().to, LifeStage::Baby);
+
+    // The life carries over through the save file
+    let saved = SaveData {
+        needs: pet.needs(),
+        life: pet.life().clone(),
+        ..Default::default()
+    };
+    let mut restored = Pet::new();
+    restored.set_life(saved.life.clone());
+    assert_eq!(restored.stage(), LifeStage::Baby);
+    assert_eq!(restored.life().history.len(), 1);
+
+    // Saves from before life stages start over as an egg
+    let old = SaveData::parse(r#"{"needs":{"hunger":50,"energy":60,"hygiene":70}}"#).unwrap();
+    assert_eq!(old.life.stage, LifeStage::Egg);
+
+    println!("Grow up test passed: {:?}", pet.life());
+}
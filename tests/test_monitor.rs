@@ -1,7 +1,17 @@
 // Integration tests for SystemMonitor
 // Tests metric collection and thresholds
 
-use sysadmin_tamagotchi::{SystemMonitor, Metrics};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use sysadmin_tamagotchi::disks::mount_for_path;
+use sysadmin_tamagotchi::junk::resolve_junk_locations_with;
+use sysadmin_tamagotchi::processes::top_n;
+use sysadmin_tamagotchi::{
+    BlockDeviceIo, CgroupSource, DiskIoSource, DiskUsage, JunkKind, JunkLocation, JunkSource,
+    LimitSource, LoadAverage, Metrics, MetricsSource, NetworkSource, NetworkUsage, Pet, PetState,
+    Pressure, PressureStall, ProcSource, ProcessSource, ProcessUsage, ScriptedSource, Smoothing,
+    SmoothingConfig, SystemMonitor, ThermalSource,
+};
 
 #[test]
 fn test_system_monitor_creation() {
@@ -86,18 +96,759 @@ fn test_metrics_refresh() {
 
 #[test]
 fn test_cleanup_temp_does_not_panic() {
-    // This test verifies that cleanup doesn't panic
-    // It may or may not delete files (depends on permissions)
-    let result = SystemMonitor::cleanup_temp();
-
-    match result {
-        Ok(deleted_mb) => {
-            println!("Cleanup test passed: {} MB deleted", deleted_mb);
-            assert!(deleted_mb >= 0);
+    // Clean a scratch tree rather than the real TEMP/trash of whoever runs the tests
+    let root = scratch_dir("cleanup");
+    let temp = root.join("tmp");
+    let trash = root.join("Trash");
+    std::fs::create_dir_all(temp.join("keep-dir")).unwrap();
+    std::fs::create_dir_all(trash.join("files/old-project")).unwrap();
+    std::fs::create_dir_all(trash.join("info")).unwrap();
+
+    let mb = vec![0u8; 1024 * 1024];
+    std::fs::write(temp.join("junk.bin"), &mb).unwrap();
+    std::fs::write(temp.join(".X0-lock"), b"1234").unwrap();
+    std::fs::write(temp.join("keep-dir/inner.bin"), &mb).unwrap();
+    std::fs::write(trash.join("files/old-project/a.bin"), &mb).unwrap();
+    std::fs::write(trash.join("info/old-project.trashinfo"), b"[Trash Info]").unwrap();
+
+    let deleted_mb = SystemMonitor::cleanup_locations(&[
+        JunkLocation {
+            kind: JunkKind::Temp,
+            path: temp.clone(),
+        },
+        JunkLocation {
+            kind: JunkKind::Trash,
+            path: trash.clone(),
+        },
+    ]);
+
+    // Top-level temp file and the whole trash go; hidden files and subdirs in temp stay
+    assert_eq!(deleted_mb, 2);
+    assert!(!temp.join("junk.bin").exists());
+    assert!(temp.join(".X0-lock").exists());
+    assert!(temp.join("keep-dir/inner.bin").exists());
+    assert!(!trash.join("files/old-project").exists());
+    assert!(!trash.join("info/old-project.trashinfo").exists());
+
+    std::fs::remove_dir_all(&root).ok();
+    println!("Cleanup test passed: {} MB deleted", deleted_mb);
+}
+
+#[test]
+fn test_junk_locations_resolve_xdg_dirs() {
+    if cfg!(windows) {
+        return;
+    }
+
+    let root = scratch_dir("xdg");
+    let cache = root.join("cache");
+    let data = root.join("data");
+    std::fs::create_dir_all(&cache).unwrap();
+    std::fs::create_dir_all(data.join("Trash")).unwrap();
+
+    let env = |key: &str| match key {
+        "XDG_CACHE_HOME" => Some(cache.to_string_lossy().into_owned()),
+        "XDG_DATA_HOME" => Some(data.to_string_lossy().into_owned()),
+        "TMPDIR" => Some("/tmp".to_string()),
+        _ => None,
+    };
+    let locations = resolve_junk_locations_with(env);
+
+    assert!(locations.contains(&JunkLocation {
+        kind: JunkKind::Cache,
+        path: cache.clone(),
+    }));
+    assert!(locations.contains(&JunkLocation {
+        kind: JunkKind::Trash,
+        path: data.join("Trash"),
+    }));
+
+    // TMPDIR=/tmp must not produce a duplicate /tmp entry
+    let tmp_count = locations
+        .iter()
+        .filter(|l| l.path == std::path::Path::new("/tmp"))
+        .count();
+    assert_eq!(tmp_count, 1);
+
+    std::fs::remove_dir_all(&root).ok();
+    println!("Junk location test passed: {:?}", locations);
+}
+
+#[test]
+fn test_junk_source_reports_per_location_sizes() {
+    let root = scratch_dir("junk-source");
+    let temp = root.join("tmp");
+    let cache = root.join("cache");
+    std::fs::create_dir_all(&temp).unwrap();
+    std::fs::create_dir_all(cache.join("app")).unwrap();
+    std::fs::write(temp.join("a.bin"), vec![0u8; 2 * 1024 * 1024]).unwrap();
+    std::fs::write(cache.join("app/b.bin"), vec![0u8; 3 * 1024 * 1024]).unwrap();
+
+    let mut monitor = SystemMonitor::with_sources(vec![Box::new(JunkSource::new(vec![
+        JunkLocation {
+            kind: JunkKind::Temp,
+            path: temp,
+        },
+        JunkLocation {
+            kind: JunkKind::Cache,
+            path: cache,
+        },
+    ]))]);
+    let metrics = wait_for_junk_scan(&mut monitor);
+    assert!(!metrics.junk_scan_incomplete);
+
+    let sizes: Vec<(JunkKind, u64)> = metrics
+        .junk_locations
+        .iter()
+        .map(|l| (l.kind, l.size_mb))
+        .collect();
+    assert_eq!(sizes, vec![(JunkKind::Temp, 2), (JunkKind::Cache, 3)]);
+    assert_eq!(metrics.disk_junk_mb, 5);
+
+    std::fs::remove_dir_all(&root).ok();
+    println!("Junk source test passed: {:?}", sizes);
+}
+
+#[test]
+fn test_scripted_source_drives_pet() {
+    let step = |ram_percent: f32, disk_junk_mb: u64| Metrics {
+        ram_percent,
+        cpu_percent: 40.0,
+        disk_junk_mb,
+        total_disk_mb: 100_000,
+        ..Default::default()
+    };
+
+    let mut monitor = SystemMonitor::with_sources(vec![Box::new(ScriptedSource::new([
+        step(50.0, 1_000),
+        step(75.0, 1_000),
+        step(96.0, 1_000),
+        step(50.0, 25_000),
+    ]))]);
+    let mut pet = Pet::new();
+
+    let mut states = Vec::new();
+    for _ in 0..5 {
+        monitor.refresh();
+        let metrics = monitor.get_metrics();
+        states.push(pet.update(&metrics));
+    }
+
+    // The last scripted step repeats once the script is exhausted
+    assert_eq!(
+        states,
+        vec![
+            PetState::Happy,
+            PetState::Okay,
+            PetState::Critical,
+            PetState::Critical,
+            PetState::Critical,
+        ]
+    );
+
+    // Junk percentage is derived by the monitor from the scripted sizes
+    let metrics = monitor.get_metrics();
+    assert!((metrics.disk_junk_percent - 25.0).abs() < 0.01);
+
+    println!("Scripted source test passed: {:?}", states);
+}
+
+#[test]
+fn test_smoothing_is_configurable_per_metric() {
+    let step = |cpu_percent: f32, ram_percent: f32, iowait_percent: f32| Metrics {
+        cpu_percent,
+        ram_percent,
+        iowait_percent,
+        swap_percent: cpu_percent,
+        ..Default::default()
+    };
+    let mut monitor = SystemMonitor::with_sources(vec![Box::new(ScriptedSource::new([
+        step(10.0, 10.0, 10.0),
+        step(20.0, 90.0, 20.0),
+        step(90.0, 20.0, 30.0),
+        step(30.0, 30.0, 40.0),
+    ]))]);
+    monitor.set_smoothing(SmoothingConfig {
+        cpu_percent: Smoothing::Window { secs: 10.0 },
+        ram_percent: Smoothing::Median { samples: 3 },
+        iowait_percent: Smoothing::Ewma {
+            half_life_secs: 5.0,
+        },
+        ..Default::default()
+    });
+
+    let start = Instant::now();
+    let samples: Vec<Metrics> = (0..4)
+        .map(|i| monitor.get_metrics_at(start + Duration::from_secs(5 * i)))
+        .collect();
+
+    // Window: the first reading drops out once it is more than 10s old
+    let cpu: Vec<f32> = samples.iter().map(|m| m.cpu_percent).collect();
+    assert_eq!(cpu, vec![10.0, 15.0, 40.0, (20.0 + 90.0 + 30.0) / 3.0]);
+
+    // Median: the single 90% spike never shows through
+    let ram: Vec<f32> = samples.iter().map(|m| m.ram_percent).collect();
+    assert_eq!(ram, vec![10.0, 50.0, 20.0, 30.0]);
+
+    // EWMA: each 5s step is one half-life, so the average moves halfway
+    let iowait: Vec<f32> = samples.iter().map(|m| m.iowait_percent).collect();
+    assert_eq!(iowait, vec![10.0, 15.0, 22.5, 31.25]);
+
+    // Unconfigured metrics pass through, and raw values are always kept
+    assert_eq!(samples[2].swap_percent, 90.0);
+    assert_eq!(samples[2].raw.cpu_percent, 90.0);
+    assert_eq!(samples[1].raw.ram_percent, 90.0);
+    assert_eq!(samples[3].raw.iowait_percent, 40.0);
+
+    println!(
+        "Smoothing test passed: cpu {:?}, ram {:?}, iowait {:?}",
+        cpu, ram, iowait
+    );
+}
+
+#[test]
+fn test_grouped_metrics_are_smoothed_per_reading() {
+    let link = |name: &str, rx_bytes_per_sec: u64| NetworkUsage {
+        interface: name.to_string(),
+        rx_bytes_per_sec,
+        tx_bytes_per_sec: 0,
+        rx_errors: 0,
+        tx_errors: 0,
+        rx_dropped: 0,
+        tx_dropped: 0,
+        faults_per_sec: 0.0,
+    };
+    let step = |load: f32, stall: f32, eth0: u64, eth1: Option<u64>| Metrics {
+        load_per_core: Some(LoadAverage {
+            one: load,
+            five: load,
+            fifteen: load,
+        }),
+        pressure: PressureStall {
+            io: Some(Pressure {
+                some_avg10: stall,
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        networks: std::iter::once(link("eth0", eth0))
+            .chain(eth1.map(|rx| link("eth1", rx)))
+            .collect(),
+        disk_io: vec![BlockDeviceIo {
+            device: "sda".to_string(),
+            read_bytes_per_sec: 123_456_789,
+            write_bytes_per_sec: 0,
+            reads_per_sec: load,
+            writes_per_sec: 0.0,
+        }],
+        ..Default::default()
+    };
+    let mut monitor = SystemMonitor::with_sources(vec![Box::new(ScriptedSource::new([
+        step(1.0, 10.0, 1000, Some(4000)),
+        step(3.0, 30.0, 3000, None),
+        step(5.0, 50.0, 5000, Some(8000)),
+    ]))]);
+    monitor.set_smoothing(SmoothingConfig {
+        load_average: Smoothing::Median { samples: 2 },
+        pressure: Smoothing::Window { secs: 60.0 },
+        network: Smoothing::Window { secs: 60.0 },
+        ..Default::default()
+    });
+
+    let start = Instant::now();
+    let samples: Vec<Metrics> = (0..3)
+        .map(|i| monitor.get_metrics_at(start + Duration::from_secs(5 * i)))
+        .collect();
+
+    let load = samples[2].load_per_core.unwrap();
+    assert_eq!((load.one, load.fifteen), (4.0, 4.0));
+    assert_eq!(samples[2].pressure.io.unwrap().some_avg10, 30.0);
+    assert_eq!(samples[2].networks[0].rx_bytes_per_sec, 3000);
+    // An interface that went away starts over when it comes back
+    assert_eq!(samples[2].networks[1].rx_bytes_per_sec, 8000);
+
+    // Unsmoothed rates are untouched, and raw copies are always kept
+    assert_eq!(samples[2].disk_io[0].read_bytes_per_sec, 123_456_789);
+    assert_eq!(samples[2].raw.load_per_core.unwrap().one, 5.0);
+    assert_eq!(samples[2].raw.networks[0].rx_bytes_per_sec, 5000);
+
+    println!("Grouped smoothing test passed");
+}
+
+#[test]
+fn test_proc_source_reads_fixture() {
+    let root = scratch_dir("proc");
+
+    std::fs::write(
+        root.join("meminfo"),
+        "MemTotal:       16000000 kB\nMemFree:         1000000 kB\nMemAvailable:    4000000 kB\n",
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("stat"),
+        "cpu  100 0 100 800 0 0 0 0 0 0\ncpu0 100 0 100 800 0 0 0 0 0 0\n",
+    )
+    .unwrap();
+
+    let source = ProcSource::with_root(&root);
+
+    // 1000 more jiffies, 250 of them idle => 75% busy
+    std::fs::write(root.join("stat"), "cpu  400 0 550 1000 50 0 0 0 0 0\n").unwrap();
+
+    let mut monitor = SystemMonitor::with_sources(vec![Box::new(source)]);
+    monitor.refresh();
+    let metrics = monitor.get_metrics();
+
+    assert!((metrics.ram_percent - 75.0).abs() < 0.01);
+    assert!((metrics.cpu_percent - 75.0).abs() < 0.01);
+    // 50 of the 1000 jiffies were iowait
+    assert!((metrics.iowait_percent - 5.0).abs() < 0.01);
+
+    std::fs::remove_dir_all(&root).ok();
+    println!(
+        "Proc source test passed: RAM={:.1}%, CPU={:.1}%",
+        metrics.ram_percent, metrics.cpu_percent
+    );
+}
+
+#[test]
+fn test_proc_source_reads_swap_and_pressure() {
+    let root = scratch_dir("proc-swap");
+    std::fs::create_dir_all(root.join("pressure")).unwrap();
+
+    std::fs::write(
+        root.join("meminfo"),
+        "MemTotal: 8000000 kB\nMemAvailable: 6000000 kB\nSwapTotal: 2097152 kB\nSwapFree: 524288 kB\n",
+    )
+    .unwrap();
+    std::fs::write(root.join("vmstat"), "pgpgin 10\npswpin 1000\npswpout 500\n").unwrap();
+    std::fs::write(
+        root.join("pressure/memory"),
+        "some avg10=12.50 avg60=4.00 avg300=1.00 total=123\nfull avg10=3.25 avg60=1.00 avg300=0.50 total=45\n",
+    )
+    .unwrap();
+    // Older kernels have no `full` line for CPU, and no io file here at all
+    std::fs::write(
+        root.join("pressure/cpu"),
+        "some avg10=30.00 avg60=20.00 avg300=10.00 total=999\n",
+    )
+    .unwrap();
+
+    let start = std::time::Instant::now();
+    let mut source = ProcSource::with_root(&root);
+    source.refresh_at(start);
+
+    std::fs::write(root.join("vmstat"), "pgpgin 10\npswpin 3000\npswpout 600\n").unwrap();
+    source.refresh_at(start + std::time::Duration::from_secs(10));
+
+    let mut metrics = Metrics::default();
+    source.collect(&mut metrics);
+
+    // RAM ignores cache: 2 of 8 GB unavailable
+    assert!((metrics.ram_percent - 25.0).abs() < 0.01);
+    assert_eq!(metrics.swap_used_mb, 1536);
+    assert!((metrics.swap_percent - 75.0).abs() < 0.01);
+    assert!((metrics.swap_in_pages_per_sec - 200.0).abs() < 0.01);
+    assert!((metrics.swap_out_pages_per_sec - 10.0).abs() < 0.01);
+
+    let memory = metrics.pressure.memory.expect("memory pressure missing");
+    assert!((memory.some_avg10 - 12.5).abs() < 0.01);
+    assert!((memory.full_avg10 - 3.25).abs() < 0.01);
+    let cpu = metrics.pressure.cpu.expect("cpu pressure missing");
+    assert!((cpu.some_avg60 - 20.0).abs() < 0.01);
+    assert_eq!(cpu.full_avg10, 0.0);
+    assert!(metrics.pressure.io.is_none());
+
+    std::fs::remove_dir_all(&root).ok();
+    println!("Swap and pressure test passed: {:?}", metrics.pressure);
+}
+
+#[test]
+fn test_thermal_source_reads_zones() {
+    let root = scratch_dir("thermal");
+    let zone = |name: &str, files: &[(&str, &str)]| {
+        let dir = root.join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file, contents) in files {
+            std::fs::write(dir.join(file), contents).unwrap();
         }
-        Err(e) => {
-            println!("Cleanup encountered error (expected on some systems): {}", e);
-            // Error is acceptable due to permissions
+    };
+
+    zone(
+        "thermal_zone0",
+        &[
+            ("type", "x86_pkg_temp\n"),
+            ("temp", "71500\n"),
+            ("trip_point_0_type", "passive\n"),
+            ("trip_point_0_temp", "90000\n"),
+            ("trip_point_1_type", "critical\n"),
+            ("trip_point_1_temp", "105000\n"),
+        ],
+    );
+    zone(
+        "thermal_zone1",
+        &[("type", "acpitz\n"), ("temp", "45000\n")],
+    );
+    // A sleeping sensor reports garbage and must not read as 0 °C
+    zone(
+        "thermal_zone2",
+        &[("type", "iwlwifi_1\n"), ("temp", "-274000\n")],
+    );
+    zone("cooling_device0", &[("type", "Processor\n")]);
+
+    let mut metrics = Metrics::default();
+    ThermalSource::with_zones_root(&root).collect(&mut metrics);
+
+    let readings: Vec<(&str, f32, Option<f32>)> = metrics
+        .temperatures
+        .iter()
+        .map(|t| (t.label.as_str(), t.celsius, t.critical_celsius))
+        .collect();
+    assert_eq!(
+        readings,
+        vec![("x86_pkg_temp", 71.5, Some(105.0)), ("acpitz", 45.0, None)]
+    );
+
+    // No sensors at all is unknown, not zero
+    let mut metrics = Metrics::default();
+    ThermalSource::with_zones_root(root.join("missing")).collect(&mut metrics);
+    assert!(metrics.temperatures.is_empty());
+
+    std::fs::remove_dir_all(&root).ok();
+    println!("Thermal source test passed: {:?}", readings);
+}
+
+#[test]
+fn test_proc_source_reads_cores_and_load() {
+    let root = scratch_dir("proc-cores");
+
+    std::fs::write(
+        root.join("meminfo"),
+        "MemTotal: 1000 kB\nMemAvailable: 500 kB\n",
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("stat"),
+        "cpu  0 0 0 400 0 0 0 0\ncpu0 0 0 0 100 0 0 0 0\ncpu1 0 0 0 100 0 0 0 0\n\
+         cpu2 0 0 0 100 0 0 0 0\ncpu3 0 0 0 100 0 0 0 0\nintr 12345\n",
+    )
+    .unwrap();
+    std::fs::write(root.join("loadavg"), "6.00 2.00 1.00 3/512 4242\n").unwrap();
+
+    let source = ProcSource::with_root(&root);
+
+    // cpu2 pegged, the other three idle: 25% overall
+    std::fs::write(
+        root.join("stat"),
+        "cpu  100 0 0 700 0 0 0 0\ncpu0 0 0 0 200 0 0 0 0\ncpu1 0 0 0 200 0 0 0 0\n\
+         cpu2 100 0 0 100 0 0 0 0\ncpu3 0 0 0 200 0 0 0 0\n",
+    )
+    .unwrap();
+
+    let mut monitor = SystemMonitor::with_sources(vec![Box::new(source)]);
+    monitor.refresh();
+    let metrics = monitor.get_metrics();
+
+    assert_eq!(metrics.per_core_percent, vec![0.0, 0.0, 100.0, 0.0]);
+    assert!((metrics.cpu_percent - 25.0).abs() < 0.01);
+    assert_eq!(metrics.hottest_core_percent, 100.0);
+    assert!(metrics.single_thread_saturated);
+
+    let load = metrics.load_average.expect("load average missing");
+    assert_eq!((load.one, load.five, load.fifteen), (6.0, 2.0, 1.0));
+    let per_core = metrics.load_per_core.expect("normalized load missing");
+    assert_eq!(
+        (per_core.one, per_core.five, per_core.fifteen),
+        (1.5, 0.5, 0.25)
+    );
+
+    std::fs::remove_dir_all(&root).ok();
+    println!(
+        "Per-core and load test passed: {:?}",
+        metrics.per_core_percent
+    );
+}
+
+#[test]
+fn test_cgroup_source_uses_effective_limits() {
+    let root = scratch_dir("cgroup");
+    let proc_root = root.join("proc");
+    let mount = root.join("cgroup");
+    let service = mount.join("system.slice/app.service");
+    std::fs::create_dir_all(proc_root.join("self")).unwrap();
+    std::fs::create_dir_all(&service).unwrap();
+
+    // 16 GiB, 8-core host
+    std::fs::write(
+        proc_root.join("self/cgroup"),
+        "0::/system.slice/app.service\n",
+    )
+    .unwrap();
+    std::fs::write(proc_root.join("meminfo"), "MemTotal: 16777216 kB\n").unwrap();
+    let stat: String = std::iter::once("cpu  0 0 0 0 0 0 0 0\n".to_string())
+        .chain((0..8).map(|i| format!("cpu{} 0 0 0 0 0 0 0 0\n", i)))
+        .collect();
+    std::fs::write(proc_root.join("stat"), stat).unwrap();
+
+    // The slice caps memory at 1 GiB; the service caps CPU at 2 cores
+    std::fs::write(mount.join("system.slice/memory.max"), "1073741824\n").unwrap();
+    std::fs::write(service.join("memory.max"), "max\n").unwrap();
+    std::fs::write(service.join("cpu.max"), "200000 100000\n").unwrap();
+    std::fs::write(service.join("memory.current"), "629145600\n").unwrap();
+    std::fs::write(
+        service.join("memory.stat"),
+        "anon 400000000\ninactive_file 92274688\n",
+    )
+    .unwrap();
+    std::fs::write(service.join("cpu.stat"), "usage_usec 5000000\n").unwrap();
+
+    let start = std::time::Instant::now();
+    let mut source = CgroupSource::with_roots(&proc_root, &mount);
+    source.refresh_at(start);
+
+    // One core's worth of CPU time over one second
+    std::fs::write(service.join("cpu.stat"), "usage_usec 6000000\n").unwrap();
+    source.refresh_at(start + std::time::Duration::from_secs(1));
+
+    let mut metrics = Metrics {
+        ram_percent: 3.0,
+        cpu_percent: 12.5,
+        ..Default::default()
+    };
+    source.collect(&mut metrics);
+
+    // (600 MiB - 88 MiB inactive cache) of 1 GiB, 1 of 2 cores
+    assert!((metrics.ram_percent - 50.0).abs() < 0.01);
+    assert!((metrics.cpu_percent - 50.0).abs() < 0.01);
+    assert_eq!(metrics.limits.memory_source, LimitSource::Cgroup);
+    assert_eq!(metrics.limits.memory_limit_mb, Some(1024));
+    assert_eq!(metrics.limits.cpu_source, LimitSource::Cgroup);
+    assert_eq!(metrics.limits.cpu_limit_cores, Some(2.0));
+    assert_eq!(
+        metrics.limits.cgroup_path.as_deref(),
+        Some("/system.slice/app.service")
+    );
+
+    // Limits above the host's capacity leave the host readings alone
+    std::fs::write(mount.join("system.slice/memory.max"), "68719476736\n").unwrap();
+    std::fs::write(service.join("cpu.max"), "max 100000\n").unwrap();
+    source.refresh_at(start + std::time::Duration::from_secs(2));
+
+    let mut metrics = Metrics {
+        ram_percent: 3.0,
+        cpu_percent: 12.5,
+        ..Default::default()
+    };
+    source.collect(&mut metrics);
+    assert_eq!((metrics.ram_percent, metrics.cpu_percent), (3.0, 12.5));
+    assert_eq!(metrics.limits.memory_source, LimitSource::Host);
+    assert_eq!(metrics.limits.cpu_source, LimitSource::Host);
+
+    std::fs::remove_dir_all(&root).ok();
+    println!("Cgroup limits test passed");
+}
+
+#[test]
+fn test_mount_resolution_uses_longest_prefix() {
+    let disks = vec![
+        DiskUsage::new("/", "ext4", 100_000, 50_000),
+        DiskUsage::new("/home", "ext4", 400_000, 100_000),
+        DiskUsage::new("/tmp", "tmpfs", 8_000, 8_000),
+    ];
+
+    let mount =
+        |path: &str| mount_for_path(&disks, Path::new(path)).map(|d| d.mount_point.as_str());
+
+    assert_eq!(mount("/home/user/.cache"), Some("/home"));
+    assert_eq!(mount("/tmp"), Some("/tmp"));
+    assert_eq!(mount("/tmpfoo/bar"), Some("/"));
+    assert_eq!(mount("/var/tmp"), Some("/"));
+
+    assert!((disks[1].used_percent - 75.0).abs() < 0.01);
+    println!("Mount resolution test passed");
+}
+
+#[test]
+fn test_top_processes_are_ranked() {
+    let process = |pid: u32, value: f32| ProcessUsage {
+        pid,
+        name: format!("proc{}", pid),
+        user: None,
+        value,
+    };
+
+    let top = top_n(
+        vec![
+            process(1, 5.0),
+            process(2, 0.0),
+            process(3, 40.0),
+            process(4, 5.0),
+            process(5, 12.5),
+        ],
+        3,
+    );
+
+    // Largest first, ties by pid, idle processes dropped
+    let pids: Vec<u32> = top.iter().map(|p| p.pid).collect();
+    assert_eq!(pids, vec![3, 5, 1]);
+    assert!(top_n(vec![process(1, 0.0)], 3).is_empty());
+
+    println!("Top process ranking test passed: {:?}", pids);
+}
+
+#[test]
+fn test_process_source_names_this_process() {
+    let mut monitor =
+        SystemMonitor::with_sources(vec![Box::new(ProcessSource::with_limit(usize::MAX))]);
+    monitor.refresh();
+    let metrics = monitor.get_metrics();
+
+    // The test binary itself has resident memory, so it must be listed
+    let me = metrics
+        .top_memory
+        .iter()
+        .find(|p| p.pid == std::process::id())
+        .expect("current process missing from top_memory");
+    assert!(me.value >= 1.0);
+    assert!(!me.name.is_empty());
+
+    let limited = {
+        let mut monitor = SystemMonitor::with_sources(vec![Box::new(ProcessSource::with_limit(2))]);
+        monitor.refresh();
+        monitor.get_metrics()
+    };
+    assert!(limited.top_cpu.len() <= 2);
+    assert!(limited.top_memory.len() <= 2);
+
+    println!("Process source test passed: {:?}", me);
+}
+
+#[test]
+fn test_network_source_reads_fixture() {
+    let root = scratch_dir("net");
+    std::fs::create_dir_all(root.join("net")).unwrap();
+
+    // Two header lines, then one line of counters per interface
+    let net_dev = |eth0: &str| {
+        [
+            "Inter-|   Receive                            |  Transmit".to_string(),
+            " face |bytes packets errs drop fifo frame compressed multicast|bytes ...".to_string(),
+            "    lo: 5000 10 0 0 0 0 0 0 5000 10 0 0 0 0 0 0".to_string(),
+            format!("  eth0: {}", eth0),
+        ]
+        .join("\n")
+    };
+    std::fs::write(
+        root.join("net/dev"),
+        net_dev("1000000 100 0 0 0 0 0 0 2000000 200 0 0 0 0 0 0"),
+    )
+    .unwrap();
+
+    let start = std::time::Instant::now();
+    let mut source = NetworkSource::with_root(&root);
+    source.refresh_at(start);
+
+    // Two seconds later: 4 MB in, 2 MB out, 3 errors and 1 drop
+    std::fs::write(
+        root.join("net/dev"),
+        net_dev("5000000 900 3 1 0 0 0 0 4000000 400 0 0 0 0 0 0"),
+    )
+    .unwrap();
+    source.refresh_at(start + std::time::Duration::from_secs(2));
+
+    let mut metrics = Metrics::default();
+    source.collect(&mut metrics);
+
+    // Loopback is left out
+    assert_eq!(metrics.networks.len(), 1);
+    let eth0 = &metrics.networks[0];
+    assert_eq!(eth0.interface, "eth0");
+    assert_eq!(eth0.rx_bytes_per_sec, 2_000_000);
+    assert_eq!(eth0.tx_bytes_per_sec, 1_000_000);
+    assert_eq!((eth0.rx_errors, eth0.rx_dropped), (3, 1));
+    assert!((eth0.faults_per_sec - 2.0).abs() < 0.01);
+
+    std::fs::remove_dir_all(&root).ok();
+    println!("Network source test passed: {:?}", eth0);
+}
+
+#[test]
+fn test_disk_io_source_reads_fixture() {
+    let root = scratch_dir("diskstats");
+
+    // Whole disks plus partitions, loop and zram devices that must be skipped
+    let diskstats = |sda: &str, nvme: &str| {
+        [
+            format!("   8       0 sda {}", sda),
+            "   8       1 sda1 500 0 4000 0 100 0 800 0 0 0 0 0 0 0 0 0 0".to_string(),
+            format!(" 259       0 nvme0n1 {}", nvme),
+            " 259       1 nvme0n1p1 9 0 9 0 9 0 9 0 0 0 0 0 0 0 0 0 0".to_string(),
+            "   7       0 loop0 9 0 9 0 9 0 9 0 0 0 0 0 0 0 0 0 0".to_string(),
+            " 252       0 zram0 9 0 9 0 9 0 9 0 0 0 0 0 0 0 0 0 0".to_string(),
+            " 253       0 dm-0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0".to_string(),
+        ]
+        .join("\n")
+    };
+    std::fs::write(
+        root.join("diskstats"),
+        diskstats(
+            "1000 0 8000 0 200 0 1600 0 0 0 0 0 0 0 0 0 0",
+            "0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0",
+        ),
+    )
+    .unwrap();
+
+    let start = std::time::Instant::now();
+    let mut source = DiskIoSource::with_root(&root);
+    source.refresh_at(start);
+
+    // Four seconds later: sda read 400 ops / 8 MiB, nvme wrote 100 ops / 2 MiB
+    std::fs::write(
+        root.join("diskstats"),
+        diskstats(
+            "1400 0 24384 0 200 0 1600 0 0 0 0 0 0 0 0 0 0",
+            "0 0 0 0 100 0 4096 0 0 0 0 0 0 0 0 0 0",
+        ),
+    )
+    .unwrap();
+    source.refresh_at(start + std::time::Duration::from_secs(4));
+
+    let mut metrics = Metrics::default();
+    source.collect(&mut metrics);
+
+    let devices: Vec<&str> = metrics.disk_io.iter().map(|d| d.device.as_str()).collect();
+    assert_eq!(devices, vec!["sda", "nvme0n1", "dm-0"]);
+
+    let sda = &metrics.disk_io[0];
+    assert_eq!(sda.read_bytes_per_sec, 2 * 1024 * 1024);
+    assert_eq!(sda.write_bytes_per_sec, 0);
+    assert!((sda.iops() - 100.0).abs() < 0.01);
+
+    let nvme = &metrics.disk_io[1];
+    assert_eq!(nvme.write_bytes_per_sec, 512 * 1024);
+    assert!((nvme.writes_per_sec - 25.0).abs() < 0.01);
+
+    std::fs::remove_dir_all(&root).ok();
+    println!("Disk I/O source test passed: {:?}", metrics.disk_io);
+}
+
+/// Poll the monitor until the background junk scan has reported
+fn wait_for_junk_scan(monitor: &mut SystemMonitor) -> Metrics {
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    loop {
+        monitor.refresh();
+        let metrics = monitor.get_metrics();
+        if !metrics.junk_scan_incomplete || std::time::Instant::now() >= deadline {
+            return metrics;
         }
+        std::thread::sleep(std::time::Duration::from_millis(20));
     }
 }
+
+/// Create an empty per-process scratch directory for a test
+fn scratch_dir(name: &str) -> std::path::PathBuf {
+    let root = std::env::temp_dir().join(format!("tamagotchi-{}-{}", name, std::process::id()));
+    std::fs::remove_dir_all(&root).ok();
+    std::fs::create_dir_all(&root).unwrap();
+    root
+}
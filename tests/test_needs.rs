@@ -0,0 +1,200 @@
+// Integration tests for the pet's needs
+// Tests decay, feeding, rest, hygiene, mood and the save file
+
+use std::time::{Duration, Instant};
+use sysadmin_tamagotchi::save::save_path_with;
+use sysadmin_tamagotchi::{Metrics, Needs, NeedsTracker, Pet, PetState, SaveData};
+
+const HOUR: Duration = Duration::from_secs(3600);
+
+fn idle(disk_junk_mb: u64) -> Metrics {
+    Metrics {
+        ram_percent: 30.0,
+        cpu_percent: 10.0,
+        disk_junk_mb,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_needs_decay_and_feeding() {
+    let start = Instant::now();
+    let busy = Metrics {
+        cpu_percent: 60.0,
+        ..idle(0)
+    };
+    let mut needs = NeedsTracker::default();
+
+    // The first update only starts the clock
+    needs.update_at(&busy, start);
+    assert_eq!(needs.needs(), Needs::default());
+
+    needs.update_at(&busy, start + 10 * HOUR);
+    let rates = *needs.rates();
+    assert_eq!(needs.needs().hunger, 100.0 - 10.0 * rates.hunger_per_hour);
+    assert_eq!(needs.needs().energy, 100.0 - 10.0 * rates.energy_per_hour);
+    assert_eq!(needs.needs().hygiene, 100.0);
+
+    // A cleanup that freed something is a meal; one that freed nothing is not
+    let hungry = needs.needs().hunger;
+    needs.feed(0);
+    assert_eq!(needs.needs().hunger, hungry);
+    needs.feed(250);
+    assert_eq!(needs.needs().hunger, hungry + rates.meal);
+
+    // Needs bottom out at zero and never overflow
+    needs.update_at(&busy, start + 100 * HOUR);
+    assert_eq!(needs.needs().hunger, 0.0);
+    for _ in 0..10 {
+        needs.feed(1);
+    }
+    assert_eq!(needs.needs().hunger, 100.0);
+
+    println!("Needs decay test passed: {:?}", needs.needs());
+}
+
+#[test]
+fn test_needs_rest_and_hygiene() {
+    let start = Instant::now();
+    let mut needs = NeedsTracker::new(Needs {
+        energy: 50.0,
+        ..Default::default()
+    });
+    let rates = *needs.rates();
+
+    // Quiet, but not yet for long enough to rest: energy still drains
+    needs.update_at(&idle(0), start);
+    let settle = Duration::from_secs_f32(rates.rest_after_secs);
+    needs.update_at(&idle(0), start + settle / 2);
+    assert!(needs.needs().energy < 50.0);
+
+    // After a sustained quiet stretch the pet recovers
+    needs.update_at(&idle(0), start + settle);
+    let rested = needs.needs().energy;
+    needs.update_at(&idle(0), start + settle + HOUR);
+    assert_eq!(needs.needs().energy, rested + rates.rest_energy_per_hour);
+
+    // A busy spell resets the quiet stretch
+    let busy = Metrics {
+        cpu_percent: 90.0,
+        ..idle(0)
+    };
+    needs.update_at(&busy, start + settle + 2 * HOUR);
+    needs.update_at(&idle(0), start + settle + 3 * HOUR);
+    assert!(needs.needs().energy < rested + rates.rest_energy_per_hour);
+
+    // Hygiene follows junk: 2 GB of new junk, then half of it cleared
+    let mut needs = NeedsTracker::default();
+    needs.update_at(&idle(1024), start);
+    needs.update_at(&idle(3072), start);
+    assert_eq!(
+        needs.needs().hygiene,
+        100.0 - 2.0 * rates.hygiene_per_junk_gb
+    );
+    needs.update_at(&idle(2048), start);
+    assert_eq!(needs.needs().hygiene, 100.0 - rates.hygiene_per_junk_gb);
+
+    println!("Needs rest and hygiene test passed");
+}
+
+#[test]
+fn test_partial_junk_scans_do_not_cost_hygiene() {
+    let start = Instant::now();
+    let scanning = |disk_junk_mb| Metrics {
+        junk_scan_incomplete: true,
+        ..idle(disk_junk_mb)
+    };
+    let mut needs = NeedsTracker::default();
+
+    // Right after a start the scan has found nothing yet, then all 10 GB
+    needs.update_at(&scanning(0), start);
+    needs.update_at(&idle(10 * 1024), start);
+    assert_eq!(needs.needs().hygiene, 100.0);
+
+    // A rescan that undercounts on the way is not junk cleared, either
+    needs.update_at(&scanning(1024), start);
+    needs.update_at(&idle(11 * 1024), start);
+    let rates = *needs.rates();
+    assert_eq!(needs.needs().hygiene, 100.0 - rates.hygiene_per_junk_gb);
+
+    println!("Partial junk scan test passed");
+}
+
+#[test]
+fn test_mood_combines_needs_and_health() {
+    let start = Instant::now();
+    let mut pet = Pet::new();
+    pet.update_at(&idle(0), start);
+    assert_eq!(pet.mood(), PetState::Happy);
+
+    // A starving pet on a healthy system is miserable
+    pet.set_needs(Needs {
+        hunger: 20.0,
+        ..Default::default()
+    });
+    assert_eq!(pet.update_at(&idle(0), start), PetState::Happy);
+    assert_eq!(pet.mood(), PetState::Critical);
+
+    // A well-fed pet on a struggling system is as bad as the system
+    pet.set_needs(Needs::default());
+    let swamped = Metrics {
+        ram_percent: 90.0,
+        ..idle(0)
+    };
+    assert_eq!(pet.update_at(&swamped, start), PetState::Stressed);
+    assert_eq!(pet.mood(), PetState::Stressed);
+
+    // Out-of-range values from an old or edited save are clamped
+    pet.set_needs(Needs {
+        hunger: 250.0,
+        energy: -5.0,
+        hygiene: f32::NAN,
+    });
+    assert_eq!(
+        pet.needs(),
+        Needs {
+            hunger: 100.0,
+            energy: 0.0,
+            hygiene: 100.0
+        }
+    );
+
+    println!("Mood test passed");
+}
+
+#[test]
+fn test_needs_survive_a_restart() {
+    let root = std::env::temp_dir().join(format!("tamagotchi-save-{}", std::process::id()));
+    std::fs::remove_dir_all(&root).ok();
+    let path = root.join("sysadmin-tamagotchi").join("save.json");
+
+    assert!(SaveData::load(&path).unwrap().is_none());
+
+    let saved = SaveData {
+        needs: Needs {
+            hunger: 42.0,
+            energy: 17.5,
+            hygiene: 80.0,
+        },
+        ..Default::default()
+    };
+    saved.save(&path).unwrap();
+    assert_eq!(SaveData::load(&path).unwrap(), Some(saved));
+
+    std::fs::write(&path, "{ not json").unwrap();
+    assert!(SaveData::load(&path).is_err());
+
+    if !cfg!(windows) {
+        let env = |key: &str| match key {
+            "HOME" => Some("/home/me".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            save_path_with(env).unwrap(),
+            std::path::Path::new("/home/me/.local/share/sysadmin-tamagotchi/save.json")
+        );
+    }
+
+    std::fs::remove_dir_all(&root).ok();
+    println!("Save file test passed");
+}
@@ -1,7 +1,12 @@
 // Integration tests for Pet state transitions
 // Tests pet behavior based on metrics
 
-use sysadmin_tamagotchi::{Pet, PetState, Metrics};
+use std::time::{Duration, Instant};
+use sysadmin_tamagotchi::{
+    CpuThresholds, DiskUsage, DwellTimes, EnabledMetrics, IoThresholds, LoadAverage, MetricStatus,
+    Metrics, NetworkThresholds, NetworkUsage, Pet, PetState, PetThresholds, Pressure,
+    PressureStall, ScoreWeights, TemperatureReading, Thresholds,
+};
 
 #[test]
 fn test_pet_creation() {
@@ -21,6 +26,7 @@ fn test_pet_happy_state() {
         disk_junk_percent: 3.0,
         disk_junk_mb: 100,
         total_disk_mb: 256000,
+        ..Default::default()
     };
 
     let state = pet.update(&metrics);
@@ -40,6 +46,7 @@ fn test_pet_okay_state() {
         disk_junk_percent: 3.0,  // Good
         disk_junk_mb: 100,
         total_disk_mb: 256000,
+        ..Default::default()
     };
 
     let state = pet.update(&metrics);
@@ -59,6 +66,7 @@ fn test_pet_stressed_state() {
         disk_junk_percent: 3.0,  // Good
         disk_junk_mb: 100,
         total_disk_mb: 256000,
+        ..Default::default()
     };
 
     let state = pet.update(&metrics);
@@ -78,6 +86,7 @@ fn test_pet_critical_state_ram() {
         disk_junk_percent: 3.0,  // Good
         disk_junk_mb: 100,
         total_disk_mb: 256000,
+        ..Default::default()
     };
 
     let state = pet.update(&metrics);
@@ -97,6 +106,7 @@ fn test_pet_critical_state_cpu() {
         disk_junk_percent: 3.0,  // Good
         disk_junk_mb: 100,
         total_disk_mb: 256000,
+        ..Default::default()
     };
 
     let state = pet.update(&metrics);
@@ -116,6 +126,7 @@ fn test_pet_critical_state_disk() {
         disk_junk_percent: 25.0,  // Critical (25% of disk)
         disk_junk_mb: 64000,  // 64GB
         total_disk_mb: 256000,  // 256GB total
+        ..Default::default()
     };
 
     let state = pet.update(&metrics);
@@ -135,6 +146,7 @@ fn test_pet_state_transitions() {
         disk_junk_percent: 3.0,
         disk_junk_mb: 100,
         total_disk_mb: 256000,
+        ..Default::default()
     };
     assert_eq!(pet.update(&happy_metrics), PetState::Happy);
 
@@ -145,6 +157,7 @@ fn test_pet_state_transitions() {
         disk_junk_percent: 3.0,
         disk_junk_mb: 100,
         total_disk_mb: 256000,
+        ..Default::default()
     };
     assert_eq!(pet.update(&okay_metrics), PetState::Okay);
 
@@ -155,6 +168,7 @@ fn test_pet_state_transitions() {
         disk_junk_percent: 3.0,
         disk_junk_mb: 100,
         total_disk_mb: 256000,
+        ..Default::default()
     };
     assert_eq!(pet.update(&stressed_metrics), PetState::Stressed);
 
@@ -165,6 +179,7 @@ fn test_pet_state_transitions() {
         disk_junk_percent: 3.0,
         disk_junk_mb: 100,
         total_disk_mb: 256000,
+        ..Default::default()
     };
     assert_eq!(pet.update(&critical_metrics), PetState::Critical);
 
@@ -186,6 +201,7 @@ fn test_updated_thresholds() {
         disk_junk_percent: 3.0,
         disk_junk_mb: 100,
         total_disk_mb: 256000,
+        ..Default::default()
     };
     pet.update(&metrics_69);
     assert_eq!(pet.get_state(), PetState::Happy);
@@ -197,6 +213,7 @@ fn test_updated_thresholds() {
         disk_junk_percent: 3.0,
         disk_junk_mb: 100,
         total_disk_mb: 256000,
+        ..Default::default()
     };
     pet.update(&metrics_70);
     assert_eq!(pet.get_state(), PetState::Okay);
@@ -208,6 +225,7 @@ fn test_updated_thresholds() {
         disk_junk_percent: 3.0,
         disk_junk_mb: 100,
         total_disk_mb: 256000,
+        ..Default::default()
     };
     pet.update(&metrics_95);
     assert_eq!(pet.get_state(), PetState::Critical);
@@ -217,3 +235,550 @@ fn test_updated_thresholds() {
     println!("  85%+ = Warning (Stressed if multiple)");
     println!("  95%+ = Critical");
 }
+
+#[test]
+fn test_metric_status_boundaries() {
+    use MetricStatus::*;
+
+    // Each level starts at its threshold; elevated defaults to 80% of warning
+    let levels = Thresholds::new(70.0, 85.0, 95.0);
+    for (value, expected) in [
+        (0.0, Good),
+        (55.9, Good),
+        (56.0, Elevated),
+        (69.9, Elevated),
+        (70.0, Warning),
+        (84.9, Warning),
+        (85.0, Stressed),
+        (94.9, Stressed),
+        (95.0, Critical),
+        (100.0, Critical),
+    ] {
+        assert_eq!(levels.classify(value), expected, "{}%", value);
+    }
+
+    let explicit = levels.with_elevated(60.0);
+    assert_eq!(explicit.classify(59.9), Good);
+    assert_eq!(explicit.classify(60.0), Elevated);
+
+    // A single stressed metric is enough to stress the pet
+    let ram = |ram_percent: f32| Metrics {
+        ram_percent,
+        cpu_percent: 20.0,
+        ..Default::default()
+    };
+    let mut pet = Pet::new();
+    assert_eq!(pet.update(&ram(60.0)), PetState::Happy);
+    assert_eq!(pet.update(&ram(84.9)), PetState::Okay);
+    assert_eq!(pet.update(&ram(85.0)), PetState::Stressed);
+    assert_eq!(
+        pet.last_transition().unwrap().reason,
+        "ram_percent stressed"
+    );
+    assert_eq!(pet.update(&ram(95.0)), PetState::Critical);
+
+    println!("Metric status boundary test passed");
+}
+
+#[test]
+fn test_pet_state_aggregation_policy() {
+    use MetricStatus::*;
+    let state = PetState::from_statuses;
+
+    assert_eq!(state(&[]), PetState::Happy);
+    assert_eq!(state(&[Good, Good]), PetState::Happy);
+    // Up to two elevated metrics are still happy, the third makes it okay
+    assert_eq!(state(&[Elevated, Elevated, Good]), PetState::Happy);
+    assert_eq!(state(&[Elevated, Elevated, Elevated]), PetState::Okay);
+    // One warning is okay, two are stressed
+    assert_eq!(state(&[Warning, Elevated, Elevated]), PetState::Okay);
+    assert_eq!(state(&[Warning, Warning]), PetState::Stressed);
+    // Worse levels count towards the milder rules too
+    assert_eq!(state(&[Stressed]), PetState::Stressed);
+    assert_eq!(state(&[Stressed, Warning]), PetState::Stressed);
+    assert_eq!(state(&[Critical]), PetState::Critical);
+    assert_eq!(state(&[Critical, Good, Elevated]), PetState::Critical);
+
+    // Three elevated metrics through the pet, each just under its warning level
+    let mut pet = Pet::new();
+    let metrics = Metrics {
+        ram_percent: 65.0,
+        cpu_percent: 65.0,
+        disk_junk_percent: 4.5,
+        ..Default::default()
+    };
+    assert_eq!(pet.update(&metrics), PetState::Okay);
+    assert_eq!(
+        pet.last_transition().unwrap().reason,
+        "ram_percent elevated, cpu_percent elevated, disk_junk_percent elevated"
+    );
+
+    println!("Aggregation policy test passed");
+}
+
+#[test]
+fn test_pet_health_score() {
+    // Sub-scores fall through 75, 50 and 25 at the warning, stressed and critical levels
+    let levels = Thresholds::new(70.0, 85.0, 95.0);
+    assert_eq!(levels.score(40.0), 100.0);
+    assert_eq!(levels.score(56.0), 100.0);
+    assert_eq!(levels.score(63.0), 87.5);
+    assert_eq!(levels.score(70.0), 75.0);
+    assert_eq!(levels.score(85.0), 50.0);
+    assert_eq!(levels.score(95.0), 25.0);
+    assert_eq!(levels.score(100.0), 12.5);
+    assert_eq!(levels.score(200.0), 0.0);
+
+    // Bands are exclusive at the bottom
+    assert_eq!(PetState::from_score(75.1), PetState::Happy);
+    assert_eq!(PetState::from_score(75.0), PetState::Okay);
+    assert_eq!(PetState::from_score(50.0), PetState::Stressed);
+    assert_eq!(PetState::from_score(25.0), PetState::Critical);
+
+    let mut pet = Pet::new();
+    assert_eq!(pet.health_score(), 100.0);
+    let calm = Metrics {
+        ram_percent: 30.0,
+        cpu_percent: 30.0,
+        ..Default::default()
+    };
+    pet.update(&calm);
+    assert_eq!(pet.health_score(), 100.0);
+
+    // One metric at warning: the mean stays high, but the score is capped to Okay
+    let busy = Metrics {
+        cpu_percent: 80.0,
+        ..calm.clone()
+    };
+    assert_eq!(pet.update(&busy), PetState::Okay);
+    assert_eq!(pet.health_score(), 75.0);
+
+    // Within a band the weights decide how low the score goes
+    let swamped = Metrics {
+        ram_percent: 90.0,
+        cpu_percent: 90.0,
+        ..calm.clone()
+    };
+    assert_eq!(pet.update(&swamped), PetState::Stressed);
+    let default_score = pet.health_score();
+    assert!(default_score > 25.0 && default_score <= 50.0);
+    pet.set_score_weights(ScoreWeights {
+        ram: 20.0,
+        cpu: 20.0,
+        ..Default::default()
+    });
+    assert_eq!(pet.update(&swamped), PetState::Stressed);
+    assert!(pet.health_score() < default_score);
+    assert!(pet.health_score() > 25.0);
+
+    // A critical metric caps the score at 25 however little it weighs
+    pet.set_score_weights(ScoreWeights {
+        ram: 0.0,
+        ..Default::default()
+    });
+    let full = Metrics {
+        ram_percent: 97.0,
+        ..calm
+    };
+    assert_eq!(pet.update(&full), PetState::Critical);
+    assert_eq!(pet.health_score(), 25.0);
+
+    println!("Health score test passed: {}", default_score);
+}
+
+#[test]
+fn test_pet_explains_its_state() {
+    let mut pet = Pet::new();
+    assert!(pet.assessments().is_empty());
+
+    let metrics = Metrics {
+        ram_percent: 40.0,
+        cpu_percent: 90.0,
+        ..Default::default()
+    };
+    assert_eq!(pet.update(&metrics), PetState::Stressed);
+
+    let find = |pet: &Pet, name: &str| {
+        pet.assessments()
+            .iter()
+            .find(|a| a.metric == name)
+            .unwrap_or_else(|| panic!("no assessment for {}", name))
+            .clone()
+    };
+    let cpu = find(&pet, "cpu_percent");
+    assert_eq!(cpu.value, Some(90.0));
+    assert_eq!(cpu.thresholds.map(|t| t.stressed), Some(85.0));
+    assert_eq!(cpu.status, MetricStatus::Stressed);
+    assert_eq!(cpu.score, Some(37.5));
+    assert!(cpu.contribution > 0.0);
+
+    let ram = find(&pet, "ram_percent");
+    assert_eq!(ram.status, MetricStatus::Good);
+    assert_eq!(ram.contribution, 0.0);
+
+    // No load average or PSI in these metrics: unknown, not good
+    let load = find(&pet, "load_per_core");
+    assert_eq!(load.value, None);
+    assert_eq!(load.score, None);
+
+    // The CPU's shortfall is the only one, and the score is capped to Stressed
+    let total: f32 = pet.assessments().iter().map(|a| a.contribution).sum();
+    assert_eq!(total, cpu.contribution);
+    assert!(pet.health_score() <= 50.0);
+
+    // Turning CPU off keeps it in the breakdown, marked as disabled
+    pet.set_enabled_metrics(EnabledMetrics {
+        cpu: false,
+        ..Default::default()
+    });
+    assert_eq!(pet.update(&metrics), PetState::Happy);
+    let cpu = find(&pet, "cpu_percent");
+    assert!(!cpu.enabled);
+    assert_eq!(cpu.value, Some(90.0));
+    assert_eq!(cpu.status, MetricStatus::Good);
+    assert_eq!(cpu.contribution, 0.0);
+
+    println!("Explain state test passed: {:?}", pet.assessments());
+}
+
+#[test]
+fn test_pet_reacts_to_full_filesystem() {
+    let mut pet = Pet::new();
+
+    // No junk to speak of, but the root filesystem is 97% used
+    let metrics = Metrics {
+        ram_percent: 50.0,
+        cpu_percent: 50.0,
+        disk_junk_percent: 1.0,
+        disk_junk_mb: 100,
+        total_disk_mb: 256000,
+        disks: vec![
+            DiskUsage::new("/", "ext4", 256000, 7680),
+            DiskUsage::new("/home", "ext4", 512000, 400000),
+        ],
+        ..Default::default()
+    };
+    assert_eq!(pet.update(&metrics), PetState::Critical);
+
+    // 85% used is only a warning
+    let metrics = Metrics {
+        disks: vec![DiskUsage::new("/", "ext4", 100000, 15000)],
+        ..metrics
+    };
+    assert_eq!(pet.update(&metrics), PetState::Okay);
+
+    println!("Full filesystem test passed");
+}
+
+#[test]
+fn test_pet_reacts_to_network_load() {
+    let link = |rx_mb: u64, faults_per_sec: f32| NetworkUsage {
+        interface: "eth0".to_string(),
+        rx_bytes_per_sec: rx_mb * 1024 * 1024,
+        tx_bytes_per_sec: 0,
+        rx_errors: 0,
+        tx_errors: 0,
+        rx_dropped: 0,
+        tx_dropped: 0,
+        faults_per_sec,
+    };
+    let with_links = |networks: Vec<NetworkUsage>| Metrics {
+        ram_percent: 50.0,
+        cpu_percent: 50.0,
+        networks,
+        ..Default::default()
+    };
+
+    let mut pet = Pet::new();
+    assert_eq!(pet.update(&with_links(vec![link(10, 0.0)])), PetState::Happy);
+    // A saturated gigabit uplink
+    assert_eq!(
+        pet.update(&with_links(vec![link(10, 0.0), link(115, 0.0)])),
+        PetState::Critical
+    );
+    // Busy link plus a NIC dropping packets
+    assert_eq!(
+        pet.update(&with_links(vec![link(70, 0.0), link(0, 5.0)])),
+        PetState::Stressed
+    );
+
+    // Thresholds are configurable, e.g. for a 10 Gbit server
+    let mut pet = Pet::with_thresholds(PetThresholds {
+        network: NetworkThresholds {
+            throughput_mb_per_sec: Thresholds::new(600.0, 900.0, 1100.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    assert_eq!(pet.update(&with_links(vec![link(115, 0.0)])), PetState::Happy);
+
+    println!("Network load test passed");
+}
+
+#[test]
+fn test_pet_reacts_to_iowait() {
+    let metrics = |iowait_percent: f32| Metrics {
+        ram_percent: 50.0,
+        cpu_percent: 20.0,
+        iowait_percent,
+        ..Default::default()
+    };
+
+    // Low CPU use but the box is stalled on storage
+    let mut pet = Pet::new();
+    assert_eq!(pet.update(&metrics(2.0)), PetState::Happy);
+    assert_eq!(pet.update(&metrics(15.0)), PetState::Okay);
+    assert_eq!(pet.update(&metrics(60.0)), PetState::Critical);
+
+    let mut pet = Pet::with_thresholds(PetThresholds {
+        io: IoThresholds {
+            iowait_percent: Thresholds::new(30.0, 60.0, 90.0),
+        },
+        ..Default::default()
+    });
+    assert_eq!(pet.update(&metrics(15.0)), PetState::Happy);
+
+    println!("I/O wait test passed");
+}
+
+#[test]
+fn test_pet_reacts_to_swap_and_pressure() {
+    let calm = Metrics {
+        ram_percent: 50.0,
+        cpu_percent: 20.0,
+        ..Default::default()
+    };
+    let mut pet = Pet::new();
+    assert_eq!(pet.update(&calm), PetState::Happy);
+
+    // Swap mostly full but idle is only a warning
+    let parked = Metrics {
+        swap_percent: 60.0,
+        ..calm.clone()
+    };
+    assert_eq!(pet.update(&parked), PetState::Okay);
+
+    // Heavy swap-in is thrashing
+    let thrashing = Metrics {
+        swap_in_pages_per_sec: 8000.0,
+        ..calm.clone()
+    };
+    assert_eq!(pet.update(&thrashing), PetState::Critical);
+
+    // Memory stalls plus I/O stalls stress the pet even with RAM looking fine
+    let stalled = Metrics {
+        pressure: PressureStall {
+            memory: Some(Pressure {
+                some_avg10: 8.0,
+                ..Default::default()
+            }),
+            io: Some(Pressure {
+                some_avg10: 12.0,
+                ..Default::default()
+            }),
+            cpu: None,
+        },
+        ..calm.clone()
+    };
+    assert_eq!(pet.update(&stalled), PetState::Stressed);
+
+    println!("Swap and pressure test passed");
+}
+
+#[test]
+fn test_pet_gets_too_hot() {
+    let with_temps = |temperatures: Vec<TemperatureReading>| Metrics {
+        ram_percent: 40.0,
+        cpu_percent: 40.0,
+        temperatures,
+        ..Default::default()
+    };
+    let sensor = |celsius: f32, critical: Option<f32>| {
+        TemperatureReading::new("cpu", celsius, critical).unwrap()
+    };
+
+    // No sensors: temperature is unknown and does not affect the mood
+    let mut pet = Pet::new();
+    assert_eq!(pet.update(&with_temps(vec![])), PetState::Happy);
+    assert_eq!(pet.is_too_hot(), None);
+
+    // Comfortably below the 100 °C limit
+    assert_eq!(
+        pet.update(&with_temps(vec![sensor(60.0, Some(100.0))])),
+        PetState::Happy
+    );
+    assert_eq!(pet.is_too_hot(), Some(false));
+
+    // 80 °C is fine for a part rated to 105 °C, but not for one rated to 90 °C
+    assert_eq!(
+        pet.update(&with_temps(vec![sensor(80.0, Some(105.0))])),
+        PetState::Happy
+    );
+    assert_eq!(pet.is_too_hot(), Some(false));
+    assert_eq!(
+        pet.update(&with_temps(vec![sensor(80.0, Some(90.0))])),
+        PetState::Stressed
+    );
+    assert_eq!(pet.is_too_hot(), Some(true));
+    assert_eq!(pet.get_emoji(), "🥵");
+
+    // Within 5 °C of shutdown is critical; sensors without a limit use absolute levels
+    assert_eq!(
+        pet.update(&with_temps(vec![
+            sensor(50.0, None),
+            sensor(96.0, Some(100.0))
+        ])),
+        PetState::Critical
+    );
+    assert_eq!(
+        pet.update(&with_temps(vec![sensor(96.0, None)])),
+        PetState::Critical
+    );
+
+    // Failed reads never become a reading
+    assert!(TemperatureReading::new("gpu", f32::NAN, None).is_none());
+    assert!(TemperatureReading::new("gpu", 0.0, None).is_none());
+
+    println!("Too hot test passed");
+}
+
+#[test]
+fn test_pet_reacts_to_load_and_hot_core() {
+    let calm = Metrics {
+        ram_percent: 40.0,
+        cpu_percent: 30.0,
+        ..Default::default()
+    };
+    let load = |one: f32| {
+        Some(LoadAverage {
+            one,
+            five: one,
+            fifteen: one,
+        })
+    };
+
+    let mut pet = Pet::new();
+    assert_eq!(pet.update(&calm), PetState::Happy);
+
+    // Twice as many runnable tasks as cores
+    let overloaded = Metrics {
+        load_per_core: load(2.5),
+        ..calm.clone()
+    };
+    assert_eq!(pet.update(&overloaded), PetState::Critical);
+
+    // One pegged core on an otherwise quiet machine is a warning
+    let single_thread = Metrics {
+        hottest_core_percent: 100.0,
+        single_thread_saturated: true,
+        load_per_core: load(0.3),
+        ..calm.clone()
+    };
+    assert_eq!(pet.update(&single_thread), PetState::Okay);
+
+    // Stricter operators can make it an alarm
+    let mut pet = Pet::with_thresholds(PetThresholds {
+        cpu: CpuThresholds {
+            hottest_core_percent: Thresholds::new(80.0, 90.0, 99.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    assert_eq!(pet.update(&single_thread), PetState::Critical);
+
+    // Without a load average (Windows) load does not count
+    assert_eq!(pet.update(&calm), PetState::Happy);
+
+    println!("Load and hot core test passed");
+}
+
+#[test]
+fn test_pet_hysteresis_stops_flicker() {
+    let cpu = |cpu_percent: f32| Metrics {
+        ram_percent: 40.0,
+        cpu_percent,
+        ..Default::default()
+    };
+
+    // Without hysteresis a CPU hovering around 70% flips on every sample
+    let mut pet = Pet::new();
+    assert_eq!(pet.update(&cpu(72.0)), PetState::Okay);
+    assert_eq!(pet.update(&cpu(68.0)), PetState::Happy);
+
+    // Entered at 70%, left only below 65%
+    let mut pet = Pet::with_thresholds(PetThresholds {
+        cpu: CpuThresholds {
+            percent: Thresholds::new(70.0, 85.0, 95.0).with_hysteresis(5.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    assert_eq!(pet.update(&cpu(68.0)), PetState::Happy);
+    assert_eq!(pet.update(&cpu(72.0)), PetState::Okay);
+    let entered = pet.last_transition().unwrap().clone();
+    assert_eq!(entered.from, PetState::Happy);
+    assert_eq!(entered.to, PetState::Okay);
+    assert_eq!(entered.reason, "cpu_percent warning");
+
+    assert_eq!(pet.update(&cpu(68.0)), PetState::Okay);
+    assert_eq!(pet.update(&cpu(66.0)), PetState::Okay);
+    assert_eq!(pet.last_transition(), Some(&entered));
+    assert_eq!(pet.update(&cpu(64.0)), PetState::Happy);
+    assert_eq!(pet.last_transition().unwrap().reason, "all metrics good");
+
+    // Critical steps down one level at a time, not straight to good
+    assert_eq!(pet.update(&cpu(96.0)), PetState::Critical);
+    assert_eq!(pet.update(&cpu(92.0)), PetState::Critical);
+    assert_eq!(pet.update(&cpu(89.0)), PetState::Stressed);
+    assert_eq!(pet.update(&cpu(79.0)), PetState::Okay);
+
+    println!("Hysteresis test passed: {:?}", pet.last_transition());
+}
+
+#[test]
+fn test_pet_dwell_times() {
+    let metrics = |ram_percent: f32, cpu_percent: f32| Metrics {
+        ram_percent,
+        cpu_percent,
+        ..Default::default()
+    };
+    let calm = metrics(40.0, 40.0);
+    let busy = metrics(40.0, 75.0);
+
+    let mut pet = Pet::with_thresholds(PetThresholds {
+        dwell: DwellTimes {
+            happy_secs: 10.0,
+            okay_secs: 30.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    let start = Instant::now();
+    let at = |secs: u64| start + Duration::from_secs(secs);
+
+    assert_eq!(pet.update_at(&calm, at(0)), PetState::Happy);
+    assert!(pet.last_transition().is_none());
+
+    // A brief blip while Happy's dwell is running is ignored
+    assert_eq!(pet.update_at(&busy, at(5)), PetState::Happy);
+    assert_eq!(pet.update_at(&busy, at(12)), PetState::Okay);
+
+    // Okay holds for 30s even after the load is gone
+    assert_eq!(pet.update_at(&calm, at(20)), PetState::Okay);
+    assert_eq!(pet.update_at(&calm, at(41)), PetState::Okay);
+    assert_eq!(pet.update_at(&calm, at(42)), PetState::Happy);
+
+    // Critical never waits
+    assert_eq!(
+        pet.update_at(&metrics(96.0, 40.0), at(43)),
+        PetState::Critical
+    );
+    let transition = pet.last_transition().unwrap();
+    assert_eq!(transition.from, PetState::Happy);
+    assert_eq!(transition.reason, "ram_percent critical");
+
+    // Zero dwell, the default, leaves Critical on the next sample
+    assert_eq!(pet.update_at(&calm, at(44)), PetState::Happy);
+
+    println!("Dwell time test passed");
+}
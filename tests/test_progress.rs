@@ -0,0 +1,137 @@
+// Integration tests for XP, levels and streaks
+// Tests XP accrual and loss, levelling, daily streaks, history and saves
+
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use sysadmin_tamagotchi::{Metrics, Pet, PetState, PetStats, Progress, ProgressTracker, SaveData};
+
+const MINUTE: Duration = Duration::from_secs(60);
+const DAY: Duration = Duration::from_secs(24 * 3600);
+
+fn cleanups(cleanups: u32) -> PetStats {
+    PetStats {
+        cleanups,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_xp_and_levels() {
+    assert_eq!(Progress::xp_for_level(1), 0.0);
+    assert_eq!(Progress::xp_for_level(2), 100.0);
+    assert_eq!(Progress::xp_for_level(4), 600.0);
+    assert_eq!(Progress::level_for(99.0), 1);
+    assert_eq!(Progress::level_for(300.0), 3);
+
+    let start = Instant::now();
+    let stats = PetStats::default();
+    let mut tracker = ProgressTracker::default();
+    tracker.update_at(PetState::Happy, stats, start);
+    assert_eq!(tracker.progress().level, 1);
+
+    // A minute Happy or Okay is worth 1 XP, Stressed earns nothing
+    tracker.update_at(PetState::Okay, stats, start + 60 * MINUTE);
+    tracker.update_at(PetState::Stressed, stats, start + 120 * MINUTE);
+    tracker.update_at(PetState::Critical, stats, start + 300 * MINUTE);
+    assert_eq!(tracker.progress().xp, 120.0);
+    assert_eq!(tracker.progress().level, 2);
+
+    // A minute Critical costs 5 XP, and XP never goes negative
+    tracker.update_at(PetState::Happy, stats, start + 310 * MINUTE);
+    assert_eq!(tracker.progress().xp, 70.0);
+    assert_eq!(tracker.progress().level, 1);
+    tracker.update_at(PetState::Critical, stats, start + 310 * MINUTE);
+    tracker.update_at(PetState::Happy, stats, start + 400 * MINUTE);
+    assert_eq!(tracker.progress().xp, 0.0);
+
+    // Levels are recorded the first time they are reached
+    tracker.update_at(PetState::Happy, stats, start + 710 * MINUTE);
+    let levels: Vec<u32> = tracker
+        .progress()
+        .level_ups
+        .iter()
+        .map(|up| up.level)
+        .collect();
+    assert_eq!(levels, [2, 3]);
+
+    println!("XP and levels test passed: {:?}", tracker.progress());
+}
+
+#[test]
+fn test_daily_streaks() {
+    let start = Instant::now();
+    let mut tracker = ProgressTracker::default();
+    // Early in a UTC day, so the minutes below stay on the same day
+    tracker.set_clock(start, UNIX_EPOCH + 20_000 * DAY + 60 * MINUTE);
+
+    // Three healthy days, each with a cleanup but the second
+    tracker.update_at(PetState::Happy, cleanups(0), start);
+    tracker.update_at(PetState::Happy, cleanups(1), start + MINUTE);
+    tracker.update_at(PetState::Happy, cleanups(1), start + DAY);
+    tracker.update_at(PetState::Okay, cleanups(2), start + 2 * DAY);
+    tracker.update_at(PetState::Happy, cleanups(3), start + 2 * DAY + MINUTE);
+    tracker.update_at(PetState::Happy, cleanups(3), start + 3 * DAY);
+    let progress = tracker.progress();
+    assert_eq!(progress.healthy_streak, 3);
+    assert_eq!(progress.cleanup_streak, 1);
+    assert_eq!(progress.best_cleanup_streak, 1);
+    let per_day: Vec<u32> = progress.days.iter().map(|d| d.cleanups).collect();
+    assert_eq!(per_day, [1, 0, 2]);
+
+    // A day with a Critical state ends the healthy streak
+    tracker.update_at(PetState::Critical, cleanups(3), start + 3 * DAY + MINUTE);
+    tracker.update_at(PetState::Happy, cleanups(3), start + 4 * DAY);
+    assert_eq!(tracker.progress().healthy_streak, 0);
+    assert_eq!(tracker.progress().best_healthy_streak, 3);
+
+    // So does a day the app never saw, once the day after it is over
+    tracker.update_at(PetState::Happy, cleanups(3), start + 5 * DAY);
+    assert_eq!(tracker.progress().healthy_streak, 1);
+    tracker.update_at(PetState::Happy, cleanups(3), start + 7 * DAY);
+    assert_eq!(tracker.progress().healthy_streak, 2);
+    tracker.update_at(PetState::Happy, cleanups(3), start + 8 * DAY);
+    assert_eq!(tracker.progress().healthy_streak, 1);
+
+    // Only the last 30 days are kept, but streaks run on
+    for day in 9..50 {
+        tracker.update_at(PetState::Happy, cleanups(3), start + day * DAY);
+    }
+    assert_eq!(tracker.progress().days.len(), 30);
+    assert_eq!(tracker.progress().healthy_streak, 42);
+
+    println!("Streak test passed");
+}
+
+#[test]
+fn test_pet_progress_survives_a_restart() {
+    let start = Instant::now();
+    let idle = Metrics {
+        ram_percent: 30.0,
+        cpu_percent: 10.0,
+        ..Default::default()
+    };
+    let mut pet = Pet::new();
+    pet.update_at(&idle, start);
+    pet.feed(50);
+    pet.update_at(&idle, start + 150 * MINUTE);
+    // Quests completed meanwhile, like keeping the CPU calm, add their reward
+    let quest_xp = 50.0 * pet.stats().quests_completed as f64;
+    assert_eq!(pet.progress().xp, 150.0 + quest_xp);
+    assert_eq!(pet.progress().level, 2);
+    assert_eq!(pet.progress().today.unwrap().cleanups, 1);
+
+    let saved = SaveData::capture(&pet, SystemTime::now());
+    let text = serde_json::to_string(&saved).unwrap();
+    let mut restored = Pet::new();
+    SaveData::parse(&text)
+        .unwrap()
+        .restore_into(&mut restored, SystemTime::now());
+    assert_eq!(restored.progress(), pet.progress());
+
+    // Restored cleanups are not counted again
+    restored.update_at(&idle, Instant::now());
+    let progress = restored.progress();
+    let days = progress.days.iter().chain(&progress.today);
+    assert_eq!(days.map(|d| d.cleanups).sum::<u32>(), 1);
+
+    println!("Progress save test passed");
+}
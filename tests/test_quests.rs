@@ -0,0 +1,160 @@
+// Integration tests for daily quests
+// Tests generation from metrics, progress tracking, rewards, new days and saves
+
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use sysadmin_tamagotchi::{Metrics, Pet, ProcessUsage, QuestGoal, QuestTracker, Quests, SaveData};
+
+const MINUTE: Duration = Duration::from_secs(60);
+const DAY: Duration = Duration::from_secs(24 * 3600);
+
+fn process(pid: u32, name: &str, memory_mb: f32) -> ProcessUsage {
+    ProcessUsage {
+        pid,
+        name: name.to_string(),
+        user: None,
+        value: memory_mb,
+    }
+}
+
+/// A machine with 5% junk, 40% CPU and a browser using 2 GB
+fn cluttered() -> Metrics {
+    Metrics {
+        ram_percent: 30.0,
+        cpu_percent: 40.0,
+        disk_junk_percent: 5.0,
+        top_memory: vec![process(42, "browser", 2048.0), process(7, "editor", 300.0)],
+        ..Default::default()
+    }
+}
+
+/// A tracker whose days start an hour before `start`
+fn tracker_at(start: Instant) -> QuestTracker {
+    let mut tracker = QuestTracker::default();
+    tracker.set_clock(start, UNIX_EPOCH + 20_000 * DAY + 60 * MINUTE);
+    tracker
+}
+
+#[test]
+fn test_quests_suit_the_machine() {
+    let quests = Quests::generate(1, &cluttered());
+    let goals: Vec<&QuestGoal> = quests.quests.iter().map(|q| &q.goal).collect();
+    assert_eq!(
+        goals,
+        [
+            &QuestGoal::TrimJunk {
+                from_percent: 5.0,
+                below_percent: 2.0
+            },
+            &QuestGoal::CalmCpu {
+                below_percent: 50.0,
+                for_secs: 3600.0
+            },
+            &QuestGoal::CloseProcess {
+                pid: 42,
+                name: "browser".to_string(),
+                memory_mb: 2048.0
+            },
+        ]
+    );
+    assert_eq!(
+        quests.quests[2].description,
+        "Close browser, which is using 2048 MB"
+    );
+
+    // A tidy machine only gets the CPU quest; unknown junk is not judged
+    let tidy = Metrics {
+        disk_junk_percent: 1.0,
+        top_memory: vec![process(7, "editor", 300.0)],
+        ..cluttered()
+    };
+    assert_eq!(Quests::generate(1, &tidy).quests.len(), 1);
+    let scanning = Metrics {
+        junk_scan_incomplete: true,
+        ..cluttered()
+    };
+    assert_eq!(Quests::generate(1, &scanning).quests.len(), 2);
+
+    println!("Quest generation test passed");
+}
+
+#[test]
+fn test_quest_progress_and_completion() {
+    let start = Instant::now();
+    let mut tracker = tracker_at(start);
+    assert!(tracker.update_at(&cluttered(), start).is_empty());
+    assert!(tracker.changed());
+    assert_eq!(tracker.quests().quests.len(), 3);
+
+    // Half the junk cleared; the CPU average holds for half an hour
+    let halfway = Metrics {
+        disk_junk_percent: 3.5,
+        ..cluttered()
+    };
+    assert!(tracker.update_at(&halfway, start + 30 * MINUTE).is_empty());
+    let progress: Vec<f32> = tracker.quests().quests.iter().map(|q| q.progress).collect();
+    assert_eq!(progress, [0.5, 0.5, 0.0]);
+
+    // Unchanged milestones are not news
+    tracker.update_at(&halfway, start + 30 * MINUTE);
+    assert!(!tracker.changed());
+
+    // The browser is closed and the junk cleared
+    let cleared = Metrics {
+        disk_junk_percent: 1.0,
+        top_memory: vec![process(7, "editor", 300.0)],
+        ..cluttered()
+    };
+    let done = tracker.update_at(&cleared, start + 40 * MINUTE);
+    assert_eq!(done.len(), 2);
+    assert!(done.iter().all(|q| q.completed_at_unix_secs.is_some()));
+
+    // An hour averaging over 50% CPU starts the window over
+    let busy = Metrics {
+        cpu_percent: 90.0,
+        ..cleared.clone()
+    };
+    assert!(tracker.update_at(&busy, start + 60 * MINUTE).is_empty());
+    assert_eq!(tracker.quests().quests[1].progress, 0.0);
+    assert!(tracker.update_at(&cleared, start + 90 * MINUTE).is_empty());
+    let done = tracker.update_at(&cleared, start + 120 * MINUTE);
+    assert_eq!(done.len(), 1);
+    assert!(tracker.quests().quests.iter().all(|q| q.progress == 1.0));
+
+    // A new day brings new quests
+    tracker.update_at(&cluttered(), start + DAY);
+    assert!(tracker.changed());
+    assert!(tracker.quests().quests.iter().all(|q| !q.is_completed()));
+
+    println!("Quest progress test passed");
+}
+
+#[test]
+fn test_completed_quests_reward_the_pet() {
+    let start = Instant::now();
+    let mut pet = Pet::new();
+    pet.update_at(&cluttered(), start);
+    let quests = pet.quests().clone();
+    assert!(!quests.quests.is_empty());
+
+    // The day's quests survive a restart
+    let saved = SaveData::capture(&pet, SystemTime::now());
+    let text = serde_json::to_string(&saved).unwrap();
+    let mut restored = Pet::new();
+    SaveData::parse(&text)
+        .unwrap()
+        .restore_into(&mut restored, SystemTime::now());
+    assert_eq!(restored.quests(), &quests);
+
+    let cleared = Metrics {
+        disk_junk_percent: 1.0,
+        top_memory: vec![process(7, "editor", 300.0)],
+        ..cluttered()
+    };
+    pet.update_at(&cleared, start + MINUTE);
+    let completed = pet.quests().quests.iter().filter(|q| q.is_completed());
+    let reward: f64 = completed.clone().map(|q| q.reward_xp).sum();
+    assert_eq!(pet.stats().quests_completed as usize, completed.count());
+    assert!(pet.progress().xp >= reward);
+
+    println!("Quest reward test passed: {:?}", pet.quests());
+}
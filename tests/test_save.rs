@@ -0,0 +1,153 @@
+// Integration tests for the save file
+// Tests versioning, migration, atomic writes, corrupt-file recovery and catch-up
+
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use sysadmin_tamagotchi::commands::AppState;
+use sysadmin_tamagotchi::{
+    LifeStage, Needs, Pet, PetState, PetStats, SaveData, SaveError, SAVE_VERSION,
+};
+
+const HOUR: Duration = Duration::from_secs(3600);
+
+/// Fresh directory for one test's save files
+fn save_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("tamagotchi-{}-{}", name, std::process::id()));
+    std::fs::remove_dir_all(&dir).ok();
+    dir
+}
+
+#[test]
+fn test_save_migrates_old_versions() {
+    // Version 1 had no version field, state, stats or timestamp
+    let v1 = r#"{
+        "needs": { "hunger": 50, "energy": 60, "hygiene": 70 },
+        "life": { "stage": "teen", "healthy_uptime_secs": 9000 }
+    }"#;
+    let migrated = SaveData::parse(v1).unwrap();
+    assert_eq!(migrated.version, SAVE_VERSION);
+    assert_eq!(migrated.state, PetState::Happy);
+    assert_eq!(migrated.needs.hunger, 50.0);
+    assert_eq!(migrated.life.stage, LifeStage::Teen);
+    assert_eq!(migrated.stats, PetStats::default());
+    assert_eq!(migrated.last_seen_unix_secs, None);
+
+    // The current version round-trips unchanged
+    let current = SaveData {
+        state: PetState::Stressed,
+        last_seen_unix_secs: Some(1_700_000_000),
+        ..migrated
+    };
+    let text = serde_json::to_string(&current).unwrap();
+    assert_eq!(SaveData::parse(&text).unwrap(), current);
+
+    // Missing fields keep their defaults
+    let partial = SaveData::parse(r#"{"version": 2, "state": "Critical"}"#).unwrap();
+    assert_eq!(partial.state, PetState::Critical);
+    assert_eq!(partial.life.stage, LifeStage::Egg);
+
+    // A save from a newer app is refused rather than misread
+    let future = format!(r#"{{"version": {}}}"#, SAVE_VERSION + 1);
+    assert!(matches!(
+        SaveData::parse(&future),
+        Err(SaveError::TooNew(v)) if v == SAVE_VERSION as u64 + 1
+    ));
+
+    println!("Save migration test passed");
+}
+
+#[test]
+fn test_save_is_atomic_and_recovers_from_corruption() {
+    let dir = save_dir("atomic");
+    let path = dir.join("save.json");
+    let backup = dir.join("save.json.bak");
+
+    let first = SaveData {
+        state: PetState::Okay,
+        ..Default::default()
+    };
+    first.save(&path).unwrap();
+    assert!(!backup.exists());
+    let second = SaveData {
+        state: PetState::Critical,
+        ..Default::default()
+    };
+    second.save(&path).unwrap();
+
+    // No temporary file is left behind, and the previous save is the backup
+    assert!(!dir.join("save.json.tmp").exists());
+    assert_eq!(SaveData::load(&path).unwrap(), Some(second));
+    assert_eq!(SaveData::load(&backup).unwrap(), Some(first.clone()));
+
+    // A truncated save is set aside and the backup restored
+    std::fs::write(&path, r#"{"version": 2, "needs": {"hun"#).unwrap();
+    let (restored, problem) = SaveData::load_or_recover(&path);
+    assert_eq!(restored, Some(first));
+    assert!(matches!(problem, Some(SaveError::Format(_))));
+    assert!(dir.join("save.json.corrupt").exists());
+    assert!(!path.exists());
+
+    // Nothing at all is a fresh start, not an error
+    let (restored, problem) = SaveData::load_or_recover(&dir.join("missing.json"));
+    assert!(restored.is_none() && problem.is_none());
+
+    std::fs::remove_dir_all(&dir).ok();
+    println!("Atomic save test passed");
+}
+
+#[test]
+fn test_newer_save_is_never_overwritten() {
+    let dir = save_dir("newer");
+    let path = dir.join("save.json");
+    std::fs::create_dir_all(&dir).unwrap();
+    let newer = r#"{"version": 99, "state": "happy"}"#;
+    std::fs::write(&path, newer).unwrap();
+
+    // The app starts a fresh pet but leaves the newer build's save alone
+    let state = AppState::with_save_path(Some(path.clone()));
+    assert!(state.save_path.is_none());
+    state.save();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), newer);
+    assert!(!dir.join("save.json.bak").exists());
+
+    std::fs::remove_dir_all(&dir).ok();
+    println!("Newer save test passed");
+}
+
+#[test]
+fn test_restore_catches_up_on_time_away() {
+    // Saves record whole seconds
+    let closed_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let mut pet = Pet::new();
+    pet.feed(120);
+    let mut saved = SaveData::capture(&pet, closed_at);
+    saved.state = PetState::Stressed;
+    saved.needs = Needs {
+        hunger: 80.0,
+        energy: 10.0,
+        hygiene: 90.0,
+    };
+
+    let mut restored = Pet::new();
+    let away = saved
+        .restore_into(&mut restored, closed_at + 10 * HOUR)
+        .unwrap();
+    assert_eq!(away, 10 * HOUR);
+    assert_eq!(restored.get_state(), PetState::Stressed);
+    assert_eq!(restored.stats().cleaned_mb, 120);
+
+    // Hungrier and older, but rested; hygiene waits for the next junk reading
+    let needs = restored.needs();
+    assert_eq!(needs.hunger, 40.0);
+    assert!(needs.energy > 10.0);
+    assert_eq!(needs.hygiene, 90.0);
+    assert_eq!(restored.life().age_secs, 36_000.0);
+
+    // Migrated saves have no timestamp and nothing to catch up on
+    saved.last_seen_unix_secs = None;
+    assert!(saved
+        .restore_into(&mut Pet::new(), SystemTime::now())
+        .is_none());
+
+    println!("Catch-up test passed: {:?}", needs);
+}
@@ -0,0 +1,271 @@
+// Integration tests for the bounded directory scanner
+// Tests limits, cancellation and cycle handling
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+use sysadmin_tamagotchi::scanner::scan_dir;
+use sysadmin_tamagotchi::{
+    JunkKind, JunkLocation, JunkSource, ScanLimits, SizeIndex, SystemMonitor, TrackingOptions,
+};
+
+/// Build a tree of `depth` nested directories, each holding one 1 KB file
+fn nested_tree(name: &str, depth: usize) -> PathBuf {
+    let root =
+        std::env::temp_dir().join(format!("tamagotchi-scan-{}-{}", name, std::process::id()));
+    std::fs::remove_dir_all(&root).ok();
+
+    let mut dir = root.clone();
+    for level in 0..depth {
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(format!("file{}.bin", level)), vec![0u8; 1024]).unwrap();
+        dir = dir.join("sub");
+    }
+    root
+}
+
+fn cleanup(root: &Path) {
+    std::fs::remove_dir_all(root).ok();
+}
+
+#[test]
+fn test_scan_counts_whole_tree() {
+    let root = nested_tree("whole", 5);
+
+    let result = scan_dir(&root, &ScanLimits::default(), &AtomicBool::new(false));
+
+    assert_eq!(result.bytes, 5 * 1024);
+    assert!(!result.incomplete);
+
+    cleanup(&root);
+    println!("Whole tree scan test passed: {:?}", result);
+}
+
+#[test]
+fn test_scan_respects_max_depth() {
+    let root = nested_tree("depth", 5);
+    let limits = ScanLimits {
+        max_depth: 2,
+        ..Default::default()
+    };
+
+    let result = scan_dir(&root, &limits, &AtomicBool::new(false));
+
+    // Root (depth 0) plus two nested levels
+    assert_eq!(result.bytes, 3 * 1024);
+    assert!(result.incomplete);
+
+    cleanup(&root);
+    println!("Max depth scan test passed: {:?}", result);
+}
+
+#[test]
+fn test_scan_respects_max_entries_and_deadline() {
+    let root = nested_tree("entries", 5);
+
+    let limits = ScanLimits {
+        max_entries: 3,
+        ..Default::default()
+    };
+    let result = scan_dir(&root, &limits, &AtomicBool::new(false));
+    assert_eq!(result.entries, 3);
+    assert!(result.incomplete);
+
+    let limits = ScanLimits {
+        time_budget: Duration::ZERO,
+        ..Default::default()
+    };
+    let result = scan_dir(&root, &limits, &AtomicBool::new(false));
+    assert_eq!(result.bytes, 0);
+    assert!(result.incomplete);
+
+    cleanup(&root);
+    println!("Entry and deadline limit test passed");
+}
+
+#[test]
+fn test_scan_stops_when_cancelled() {
+    let root = nested_tree("cancel", 3);
+
+    let result = scan_dir(&root, &ScanLimits::default(), &AtomicBool::new(true));
+
+    assert_eq!(result.entries, 0);
+    assert!(result.incomplete);
+
+    cleanup(&root);
+    println!("Cancellation test passed");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_scan_survives_symlink_loop() {
+    let root = nested_tree("loop", 2);
+    std::os::unix::fs::symlink(&root, root.join("sub/back-to-root")).unwrap();
+
+    let result = scan_dir(&root, &ScanLimits::default(), &AtomicBool::new(false));
+
+    assert_eq!(result.bytes, 2 * 1024);
+    assert!(!result.incomplete);
+
+    cleanup(&root);
+    println!("Symlink loop test passed: {:?}", result);
+}
+
+#[test]
+fn test_size_index_applies_changes() {
+    let root = nested_tree("index", 2);
+    let cancel = AtomicBool::new(false);
+    let limits = ScanLimits::default();
+
+    let (mut index, dirs) = SizeIndex::build(&root, &limits, &cancel);
+    assert_eq!(index.total_bytes(), 2 * 1024);
+    assert_eq!(dirs, vec![root.clone(), root.join("sub")]);
+
+    // File grows
+    std::fs::write(root.join("file0.bin"), vec![0u8; 4096]).unwrap();
+    index.update_path(&root.join("file0.bin"), &limits, &cancel);
+    assert_eq!(index.total_bytes(), 5 * 1024);
+
+    // Directory moved in from elsewhere
+    std::fs::create_dir_all(root.join("new/deeper")).unwrap();
+    std::fs::write(root.join("new/deeper/c.bin"), vec![0u8; 1024]).unwrap();
+    let new_dirs = index.update_path(&root.join("new"), &limits, &cancel);
+    assert_eq!(new_dirs, vec![root.join("new"), root.join("new/deeper")]);
+    assert_eq!(index.total_bytes(), 6 * 1024);
+
+    // Directory deleted: everything below it is forgotten, but not a
+    // sibling whose name merely starts the same
+    std::fs::write(root.join("sub.bin"), vec![0u8; 1024]).unwrap();
+    index.update_path(&root.join("sub.bin"), &limits, &cancel);
+    std::fs::remove_dir_all(root.join("sub")).unwrap();
+    index.update_path(&root.join("sub"), &limits, &cancel);
+    assert_eq!(index.total_bytes(), 6 * 1024);
+
+    // File deleted
+    std::fs::remove_file(root.join("sub.bin")).unwrap();
+    index.update_path(&root.join("sub.bin"), &limits, &cancel);
+    assert_eq!(index.total_bytes(), 5 * 1024);
+
+    cleanup(&root);
+    println!("Size index test passed");
+}
+
+#[test]
+fn test_junk_source_tracks_changes_without_rescan() {
+    let root = nested_tree("tracker", 1);
+    let location = JunkLocation {
+        kind: JunkKind::Temp,
+        path: root.clone(),
+    };
+
+    // A rescan interval this long means only notifications can move the total
+    let options = TrackingOptions {
+        rescan_interval: Duration::from_secs(3600),
+        ..Default::default()
+    };
+    let mut monitor = SystemMonitor::with_sources(vec![Box::new(JunkSource::with_options(
+        vec![location],
+        options,
+    ))]);
+
+    let first = poll_junk(&mut monitor, |m| !m.junk_scan_incomplete);
+    assert_eq!(first.disk_junk_mb, 0);
+
+    if !first.junk_locations[0].watched {
+        // No inotify on this platform/sandbox; rescans are the fallback
+        cleanup(&root);
+        println!("Change tracking test skipped: notifications unavailable");
+        return;
+    }
+
+    std::fs::create_dir_all(root.join("build")).unwrap();
+    std::fs::write(root.join("build/out.bin"), vec![0u8; 3 * 1024 * 1024]).unwrap();
+
+    let updated = poll_junk(&mut monitor, |m| m.disk_junk_mb == 3);
+    assert_eq!(updated.disk_junk_mb, 3);
+
+    std::fs::remove_dir_all(root.join("build")).unwrap();
+    let removed = poll_junk(&mut monitor, |m| m.disk_junk_mb == 0);
+    assert_eq!(removed.disk_junk_mb, 0);
+
+    cleanup(&root);
+    println!("Change tracking test passed");
+}
+
+#[test]
+fn test_junk_source_rescans_when_not_watching() {
+    let root = nested_tree("rescan", 1);
+    let location = JunkLocation {
+        kind: JunkKind::Temp,
+        path: root.clone(),
+    };
+
+    let options = TrackingOptions {
+        rescan_interval: Duration::ZERO,
+        watch: false,
+        ..Default::default()
+    };
+    let mut monitor = SystemMonitor::with_sources(vec![Box::new(JunkSource::with_options(
+        vec![location],
+        options,
+    ))]);
+
+    let first = poll_junk(&mut monitor, |m| !m.junk_scan_incomplete);
+    assert!(!first.junk_locations[0].watched);
+
+    std::fs::write(root.join("big.bin"), vec![0u8; 2 * 1024 * 1024]).unwrap();
+    let updated = poll_junk(&mut monitor, |m| m.disk_junk_mb == 2);
+    assert_eq!(updated.disk_junk_mb, 2);
+
+    cleanup(&root);
+    println!("Rescan fallback test passed");
+}
+
+#[test]
+fn test_incomplete_location_stays_on_rescans() {
+    let root = nested_tree("partial", 3);
+    std::fs::write(root.join("big.bin"), vec![0u8; 2 * 1024 * 1024]).unwrap();
+    let location = JunkLocation {
+        kind: JunkKind::Temp,
+        path: root.clone(),
+    };
+
+    // Too shallow to reach the nested files, so every walk is partial
+    let options = TrackingOptions {
+        limits: ScanLimits {
+            max_depth: 0,
+            ..Default::default()
+        },
+        rescan_interval: Duration::ZERO,
+        ..Default::default()
+    };
+    let mut monitor = SystemMonitor::with_sources(vec![Box::new(JunkSource::with_options(
+        vec![location],
+        options,
+    ))]);
+
+    let scanned = poll_junk(&mut monitor, |m| m.disk_junk_mb == 2);
+    assert_eq!(scanned.disk_junk_mb, 2);
+    assert!(scanned.junk_scan_incomplete);
+    // Notifications alone would never fill in the rest
+    assert!(!scanned.junk_locations[0].watched);
+
+    cleanup(&root);
+    println!("Incomplete location rescan test passed");
+}
+
+/// Refresh the monitor until `done` holds or five seconds pass
+fn poll_junk(
+    monitor: &mut SystemMonitor,
+    done: impl Fn(&sysadmin_tamagotchi::Metrics) -> bool,
+) -> sysadmin_tamagotchi::Metrics {
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    loop {
+        monitor.refresh();
+        let metrics = monitor.get_metrics();
+        if done(&metrics) || std::time::Instant::now() >= deadline {
+            return metrics;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}